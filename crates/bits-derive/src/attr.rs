@@ -0,0 +1,196 @@
+use proc_macro2::Span;
+use syn::{
+    Attribute, Expr, ExprLit, Lit, LitStr, Pat, Path, Token, Type, meta::ParseNestedMeta,
+    parse::ParseStream, parse_quote,
+};
+
+use crate::flavor::Flavor;
+
+/// Constructor tag, i.e. `tag$10` or `tag#0f` in TL-B
+#[derive(Clone, Copy)]
+pub struct Tag {
+    pub value: u128,
+    pub len: usize,
+    pub span: Span,
+}
+
+impl Tag {
+    /// Parses tags given as string or integer literals in the following
+    /// formats, leading zeros are significant:
+    /// * `0b1010` or `"$1010"`: binary, one bit per digit
+    /// * `0x0f` or `"#0f"`: hex, four bits per digit
+    pub fn parse(lit: &Lit) -> syn::Result<Self> {
+        let s = match lit {
+            Lit::Str(lit) => lit.value(),
+            Lit::Int(int) => int.to_string().trim_end_matches(int.suffix()).to_string(),
+            _ => return Err(syn::Error::new_spanned(lit, "expected string or integer")),
+        };
+        let (radix, digits) = if let Some(d) = s.strip_prefix("0b").or(s.strip_prefix('$')) {
+            (2, d)
+        } else if let Some(d) = s.strip_prefix("0x").or(s.strip_prefix('#')) {
+            (16, d)
+        } else {
+            return Err(syn::Error::new_spanned(
+                lit,
+                "tag should start with one of: `0b`, `$`, `0x`, `#`",
+            ));
+        };
+        let digits: String = digits.chars().filter(|c| *c != '_').collect();
+        if digits.is_empty() {
+            return Err(syn::Error::new_spanned(lit, "empty tag"));
+        }
+        let len = digits.len() * if radix == 2 { 1 } else { 4 };
+        if len > 128 {
+            return Err(syn::Error::new_spanned(lit, "tag is longer than 128 bits"));
+        }
+        let value = u128::from_str_radix(&digits, radix)
+            .map_err(|err| syn::Error::new_spanned(lit, format!("invalid tag: {err}")))?;
+        Ok(Self {
+            value,
+            len,
+            span: lit.span(),
+        })
+    }
+
+    /// Returns whether `self` is a prefix of `other`
+    pub fn is_prefix_of(&self, other: &Self) -> bool {
+        self.len <= other.len && other.value >> (other.len - self.len) == self.value
+    }
+}
+
+/// Attributes on struct or enum
+pub struct ContainerAttrs {
+    /// `#[bits(crate = "::tlbits")]` or `#[tlb(crate = "::tlb")]`
+    pub crate_path: Path,
+    /// `#[bits(tag = "0b10")]`, structs only
+    pub tag: Option<Tag>,
+    /// `#[bits(args = "(a, b): (u32, bool)")]`
+    pub args: Option<(Pat, Type)>,
+}
+
+impl ContainerAttrs {
+    pub fn parse(attrs: &[Attribute], flavor: Flavor) -> syn::Result<Self> {
+        let mut s = Self {
+            crate_path: flavor.default_crate(),
+            tag: None,
+            args: None,
+        };
+        for_each_meta(attrs, flavor, |meta| {
+            if meta.path.is_ident("crate") {
+                s.crate_path = meta.value()?.parse::<LitStr>()?.parse()?;
+            } else if meta.path.is_ident("tag") {
+                s.tag = Some(Tag::parse(&meta.value()?.parse()?)?);
+            } else if meta.path.is_ident("args") {
+                s.args = Some(meta.value()?.parse::<LitStr>()?.parse_with(
+                    |input: ParseStream| {
+                        let pat = Pat::parse_single(input)?;
+                        input.parse::<Token![:]>()?;
+                        Ok((pat, input.parse()?))
+                    },
+                )?);
+            } else {
+                return Err(meta.error("unknown attribute"));
+            }
+            Ok(())
+        })?;
+        Ok(s)
+    }
+}
+
+/// Attributes on enum variant
+pub struct VariantAttrs {
+    /// `#[bits(tag = "0b10")]`
+    pub tag: Option<Tag>,
+}
+
+impl VariantAttrs {
+    pub fn parse(attrs: &[Attribute], flavor: Flavor) -> syn::Result<Self> {
+        let mut s = Self { tag: None };
+        for_each_meta(attrs, flavor, |meta| {
+            if meta.path.is_ident("tag") {
+                s.tag = Some(Tag::parse(&meta.value()?.parse()?)?);
+            } else {
+                return Err(meta.error("unknown attribute"));
+            }
+            Ok(())
+        })?;
+        Ok(s)
+    }
+}
+
+/// Attributes on field
+pub struct FieldAttrs {
+    /// `#[bits(as = "NBits<7>")]`
+    pub r#as: Option<Type>,
+    /// `#[bits(args = 8)]` or `#[bits(args = "(8, ())")]`
+    pub args: Option<Expr>,
+    /// `#[tlb(data)]`: wrap adapter in `Data<...>`
+    pub data: bool,
+    /// `#[tlb(ref)]`: wrap adapter in `Ref<...>`
+    pub r#ref: bool,
+    /// `#[bits(context = "action_send_msg")]`: error context instead of
+    /// the field name
+    pub context: Option<LitStr>,
+}
+
+impl FieldAttrs {
+    pub fn parse(attrs: &[Attribute], flavor: Flavor) -> syn::Result<Self> {
+        let mut s = Self {
+            r#as: None,
+            args: None,
+            data: false,
+            r#ref: false,
+            context: None,
+        };
+        for_each_meta(attrs, flavor, |meta| {
+            if flavor == Flavor::Cell && meta.path.is_ident("data") {
+                s.data = true;
+            } else if flavor == Flavor::Cell && meta.path.is_ident("ref") {
+                s.r#ref = true;
+            } else if meta.path.is_ident("as") {
+                s.r#as = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+            } else if meta.path.is_ident("context") {
+                s.context = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("args") {
+                s.args = Some(match meta.value()?.parse()? {
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(lit), ..
+                    }) => lit.parse()?,
+                    expr => expr,
+                });
+            } else {
+                return Err(meta.error("unknown attribute"));
+            }
+            Ok(())
+        })?;
+        Ok(s)
+    }
+
+    /// Resulting adapter: `as`, wrapped in `Data` and `Ref` if specified
+    pub fn adapter(&self, krate: &Path) -> Option<Type> {
+        let mut r#as = self.r#as.clone();
+        if self.data {
+            let inner = r#as.unwrap_or_else(|| parse_quote!(#krate::Same));
+            r#as = Some(parse_quote!(#krate::Data<#inner>));
+        }
+        if self.r#ref {
+            let inner = r#as.unwrap_or_else(|| parse_quote!(#krate::Same));
+            r#as = Some(parse_quote!(#krate::Ref<#inner>));
+        }
+        r#as
+    }
+}
+
+fn for_each_meta(
+    attrs: &[Attribute],
+    flavor: Flavor,
+    mut f: impl FnMut(ParseNestedMeta) -> syn::Result<()>,
+) -> syn::Result<()> {
+    for attr in attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident(flavor.attr()))
+    {
+        attr.parse_nested_meta(&mut f)?;
+    }
+    Ok(())
+}
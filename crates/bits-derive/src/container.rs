@@ -0,0 +1,230 @@
+use std::collections::HashSet;
+
+use proc_macro2::{Ident, Span, TokenStream, TokenTree};
+use quote::{ToTokens, format_ident, quote};
+use syn::{
+    Data, DeriveInput, Fields, Generics, Lifetime, LifetimeParam, Member, Path, Type,
+    WherePredicate,
+};
+
+use crate::{
+    attr::{ContainerAttrs, FieldAttrs, Tag, VariantAttrs},
+    flavor::Flavor,
+};
+
+/// Parsed struct or enum
+pub struct Container {
+    pub flavor: Flavor,
+    pub ident: Ident,
+    pub generics: Generics,
+    pub attrs: ContainerAttrs,
+    pub kind: Kind,
+}
+
+pub enum Kind {
+    Struct(Variant),
+    Enum(Vec<Variant>),
+}
+
+/// Struct or enum variant
+pub struct Variant {
+    /// [`None`] for structs
+    pub ident: Option<Ident>,
+    pub tag: Option<Tag>,
+    pub fields: Vec<Field>,
+}
+
+pub struct Field {
+    pub member: Member,
+    pub ty: Type,
+    pub attrs: FieldAttrs,
+    /// Adapter to **de**/**ser**ialize the field with
+    pub adapter: Option<Type>,
+    /// Local variable the field is bound to
+    pub binding: Ident,
+}
+
+impl Container {
+    pub fn from_ast(input: DeriveInput, flavor: Flavor) -> syn::Result<Self> {
+        let attrs = ContainerAttrs::parse(&input.attrs, flavor)?;
+        let krate = &attrs.crate_path;
+        let kind = match input.data {
+            Data::Struct(data) => Kind::Struct(Variant {
+                ident: None,
+                tag: attrs.tag,
+                fields: Field::from_ast(data.fields, flavor, krate)?,
+            }),
+            Data::Enum(data) => {
+                if let Some(tag) = attrs.tag {
+                    return Err(syn::Error::new(
+                        tag.span,
+                        "tags on enums should be specified for each variant",
+                    ));
+                }
+                let variants = data
+                    .variants
+                    .into_iter()
+                    .map(|v| {
+                        let tag = VariantAttrs::parse(&v.attrs, flavor)?.tag.ok_or_else(|| {
+                            syn::Error::new_spanned(
+                                &v.ident,
+                                format!("missing #[{}(tag = ...)]", flavor.attr()),
+                            )
+                        })?;
+                        Ok(Variant {
+                            ident: Some(v.ident),
+                            tag: Some(tag),
+                            fields: Field::from_ast(v.fields, flavor, krate)?,
+                        })
+                    })
+                    .collect::<syn::Result<Vec<_>>>()?;
+                ensure_prefix_free(&variants)?;
+                Kind::Enum(variants)
+            }
+            Data::Union(data) => {
+                return Err(syn::Error::new_spanned(
+                    data.union_token,
+                    "unions are not supported",
+                ));
+            }
+        };
+        Ok(Self {
+            flavor,
+            ident: input.ident,
+            generics: input.generics,
+            attrs,
+            kind,
+        })
+    }
+
+    pub fn variants(&self) -> &[Variant] {
+        match &self.kind {
+            Kind::Struct(v) => core::slice::from_ref(v),
+            Kind::Enum(vs) => vs,
+        }
+    }
+
+    /// Generics with additional `'de` lifetime outliving all the others
+    pub fn de_generics(&self) -> (Generics, Lifetime) {
+        let taken: HashSet<_> = self
+            .generics
+            .lifetimes()
+            .map(|l| l.lifetime.ident.to_string())
+            .collect();
+        let de = Lifetime::new(
+            if taken.contains("de") { "'__de" } else { "'de" },
+            Span::call_site(),
+        );
+        let mut param = LifetimeParam::new(de.clone());
+        param
+            .bounds
+            .extend(self.generics.lifetimes().map(|l| l.lifetime.clone()));
+        let mut generics = self.generics.clone();
+        generics.params.insert(0, param.into());
+        (generics, de)
+    }
+
+    /// Adds predicates produced by `f` for each field whose type depends
+    /// on generic type parameters.
+    pub fn with_bounds(
+        &self,
+        mut generics: Generics,
+        f: impl Fn(&Field) -> Vec<WherePredicate>,
+    ) -> Generics {
+        let params: HashSet<_> = self.generics.type_params().map(|p| &p.ident).collect();
+        let predicates: Vec<_> = self
+            .variants()
+            .iter()
+            .flat_map(|v| &v.fields)
+            .filter(|f| {
+                mentions(f.ty.to_token_stream(), &params)
+                    || f.adapter
+                        .as_ref()
+                        .is_some_and(|a| mentions(a.to_token_stream(), &params))
+            })
+            .flat_map(f)
+            .collect();
+        generics.make_where_clause().predicates.extend(predicates);
+        generics
+    }
+
+    /// `type Args` and a pattern to bind it to
+    pub fn args(&self) -> (TokenStream, TokenStream) {
+        match &self.attrs.args {
+            Some((pat, ty)) => (ty.to_token_stream(), pat.to_token_stream()),
+            None => (quote!(()), quote!(_)),
+        }
+    }
+}
+
+impl Variant {
+    /// `Self` or `Self::Variant`
+    pub fn path(&self) -> TokenStream {
+        match &self.ident {
+            Some(ident) => quote!(Self::#ident),
+            None => quote!(Self),
+        }
+    }
+
+    /// `Self::Variant { a: __f0, 1: __f1 }`, which is valid both as a
+    /// pattern and as an expression for all kinds of fields
+    pub fn destructure(&self) -> TokenStream {
+        let path = self.path();
+        let members = self.fields.iter().map(|f| &f.member);
+        let bindings = self.fields.iter().map(|f| &f.binding);
+        quote!(#path { #(#members: #bindings),* })
+    }
+}
+
+impl Field {
+    fn from_ast(fields: Fields, flavor: Flavor, krate: &Path) -> syn::Result<Vec<Self>> {
+        fields
+            .into_iter()
+            .enumerate()
+            .map(|(i, f)| {
+                let attrs = FieldAttrs::parse(&f.attrs, flavor)?;
+                Ok(Self {
+                    member: f.ident.map_or_else(|| Member::from(i), Member::from),
+                    ty: f.ty,
+                    adapter: attrs.adapter(krate),
+                    attrs,
+                    binding: format_ident!("__f{i}"),
+                })
+            })
+            .collect()
+    }
+
+    /// Field name to be used in error context
+    pub fn name(&self) -> String {
+        if let Some(context) = &self.attrs.context {
+            return context.value();
+        }
+        match &self.member {
+            Member::Named(ident) => ident.to_string(),
+            Member::Unnamed(index) => index.index.to_string(),
+        }
+    }
+}
+
+fn ensure_prefix_free(variants: &[Variant]) -> syn::Result<()> {
+    let tags: Vec<Tag> = variants.iter().filter_map(|v| v.tag).collect();
+    for (i, a) in tags.iter().enumerate() {
+        for b in &tags[i + 1..] {
+            if a.is_prefix_of(b) || b.is_prefix_of(a) {
+                return Err(syn::Error::new(
+                    b.span,
+                    "ambiguous tag: conflicts with tag of another variant",
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn mentions(tokens: TokenStream, idents: &HashSet<&Ident>) -> bool {
+    tokens.into_iter().any(|tt| match tt {
+        TokenTree::Ident(ident) => idents.contains(&ident),
+        TokenTree::Group(group) => mentions(group.stream(), idents),
+        _ => false,
+    })
+}
@@ -0,0 +1,69 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Lifetime, Path, Type, parse_quote};
+
+/// Kind of traits to derive
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Flavor {
+    /// `tlbits::ser::BitPack` and `tlbits::de::BitUnpack`
+    Bits,
+    /// `tlb::ser::CellSerialize` and `tlb::de::CellDeserialize`
+    Cell,
+}
+
+impl Flavor {
+    /// Name of helper attribute
+    pub const fn attr(self) -> &'static str {
+        match self {
+            Self::Bits => "bits",
+            Self::Cell => "tlb",
+        }
+    }
+
+    pub fn default_crate(self) -> Path {
+        match self {
+            Self::Bits => parse_quote!(::tlbits),
+            Self::Cell => parse_quote!(::tlb),
+        }
+    }
+
+    /// Path to `tlbits` given the path to the crate
+    pub fn bits(self, krate: &Path) -> TokenStream {
+        match self {
+            Self::Bits => quote!(#krate),
+            Self::Cell => quote!(#krate::bits),
+        }
+    }
+
+    /// `BitPack` or `CellSerialize`
+    pub fn ser_trait(self, krate: &Path) -> TokenStream {
+        match self {
+            Self::Bits => quote!(#krate::ser::BitPack),
+            Self::Cell => quote!(#krate::ser::CellSerialize),
+        }
+    }
+
+    /// `BitPackAs<T>` or `CellSerializeAs<T>`
+    pub fn ser_as_trait(self, krate: &Path, ty: &Type) -> TokenStream {
+        match self {
+            Self::Bits => quote!(#krate::ser::BitPackAs<#ty>),
+            Self::Cell => quote!(#krate::ser::CellSerializeAs<#ty>),
+        }
+    }
+
+    /// `BitUnpack<'de>` or `CellDeserialize<'de>`
+    pub fn de_trait(self, krate: &Path, de: &Lifetime) -> TokenStream {
+        match self {
+            Self::Bits => quote!(#krate::de::BitUnpack<#de>),
+            Self::Cell => quote!(#krate::de::CellDeserialize<#de>),
+        }
+    }
+
+    /// `BitUnpackAs<'de, T>` or `CellDeserializeAs<'de, T>`
+    pub fn de_as_trait(self, krate: &Path, de: &Lifetime, ty: &Type) -> TokenStream {
+        match self {
+            Self::Bits => quote!(#krate::de::BitUnpackAs<#de, #ty>),
+            Self::Cell => quote!(#krate::de::CellDeserializeAs<#de, #ty>),
+        }
+    }
+}
@@ -0,0 +1,168 @@
+#![doc = include_str!("../README.md")]
+mod attr;
+mod container;
+mod flavor;
+mod pack;
+mod unpack;
+
+use proc_macro::TokenStream;
+use syn::{DeriveInput, parse_macro_input};
+
+use self::flavor::Flavor;
+
+/// Derive [`BitPack`](https://docs.rs/tlbits/latest/tlbits/ser/trait.BitPack.html)
+///
+/// See [`BitUnpack`](macro@BitUnpack) for the list of supported attributes.
+#[proc_macro_derive(BitPack, attributes(bits))]
+pub fn derive_bit_pack(input: TokenStream) -> TokenStream {
+    pack::expand(parse_macro_input!(input as DeriveInput), Flavor::Bits)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive [`BitUnpack`](https://docs.rs/tlbits/latest/tlbits/de/trait.BitUnpack.html)
+///
+/// Fields are **de**/**ser**ialized one by one in order of declaration.
+/// Errors are wrapped in context of the field name, or its index for
+/// tuple fields, unless overridden with `context` attribute.
+///
+/// ## Container attributes
+///
+/// * `#[bits(tag = "0b10")]`: constructor tag of the struct. Binary
+///   (`0b10` or `$10`) and hex (`0x0f` or `#0f`) notations are supported.
+/// * `#[bits(args = "(len, flag): (usize, bool)")]`: pattern and type of
+///   [`Args`](https://docs.rs/tlbits/latest/tlbits/ser/trait.BitPack.html#associatedtype.Args).
+///   Bindings from the pattern can be used in `args` of the fields.
+///   Defaults to `()`.
+/// * `#[bits(crate = "::tlb::bits")]`: path to `tlbits` crate.
+///
+/// ## Variant attributes
+///
+/// * `#[bits(tag = "0b10")]`: constructor tag of the variant, required
+///   for all variants. Tags should be prefix-free.
+///
+/// ## Field attributes
+///
+/// * `#[bits(as = "NBits<7>")]`: **de**/**ser**ialize using an adapter.
+/// * `#[bits(args = 8)]`: args to **de**/**ser**ialize the field with.
+///   Defaults to [`NoArgs::EMPTY`](https://docs.rs/tlbits/latest/tlbits/trait.NoArgs.html).
+/// * `#[bits(context = "send_msg")]`: error context of the field.
+///   Defaults to the field name.
+///
+/// ## Lifetimes
+///
+/// Generated `impl<'de, ...> BitUnpack<'de>` requires `'de` to outlive
+/// all lifetimes of the type, so borrowed fields can be unpacked without
+/// copying:
+///
+/// ```rust
+/// # use std::borrow::Cow;
+/// # use tlbits::{
+/// #   bitvec::{order::Msb0, slice::BitSlice},
+/// #   BorrowCow, NBits,
+/// #   de::{BitUnpack, unpack_fully},
+/// #   ser::{BitPack, pack},
+/// #   StringError,
+/// # };
+/// /// ```tlb
+/// /// data$10 len:(## 7) bits:(len * Bit) = Data;
+/// /// ```
+/// #[derive(Debug, PartialEq, BitPack, BitUnpack)]
+/// #[bits(tag = "0b10", args = "len: usize")]
+/// struct Data<'a> {
+///     #[bits(as = "NBits<7>")]
+///     len: u8,
+///     #[bits(as = "BorrowCow", args = len)]
+///     bits: Cow<'a, BitSlice<u8, Msb0>>,
+/// }
+/// # fn main() -> Result<(), StringError> {
+/// # let bits = tlbits::bitvec::bits![u8, Msb0; 1, 0, 1];
+/// # let data = Data { len: 3, bits: Cow::Borrowed(bits) };
+/// # let packed = pack(&data, 3)?;
+/// # assert_eq!(unpack_fully::<Data>(&packed, 3)?, data);
+/// # Ok(())
+/// # }
+/// ```
+#[proc_macro_derive(BitUnpack, attributes(bits))]
+pub fn derive_bit_unpack(input: TokenStream) -> TokenStream {
+    unpack::expand(parse_macro_input!(input as DeriveInput), Flavor::Bits)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive [`CellSerialize`](https://docs.rs/tlb/latest/tlb/ser/trait.CellSerialize.html)
+///
+/// See [`CellDeserialize`](macro@CellDeserialize) for the list of supported attributes.
+#[proc_macro_derive(CellSerialize, attributes(tlb))]
+pub fn derive_cell_serialize(input: TokenStream) -> TokenStream {
+    pack::expand(parse_macro_input!(input as DeriveInput), Flavor::Cell)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive [`CellDeserialize`](https://docs.rs/tlb/latest/tlb/de/trait.CellDeserialize.html)
+///
+/// Supports the same attributes as [`BitUnpack`](macro@BitUnpack), but
+/// under `#[tlb(...)]`, and with `crate` defaulting to `::tlb`.
+/// Tags can also be specified as integer literals, e.g. `#[tlb(tag = 0x0f8a7ea5)]`
+/// for 32-bit opcodes, where leading zeros are significant.
+///
+/// Fields are **de**/**ser**ialized with
+/// [`CellSerialize`](https://docs.rs/tlb/latest/tlb/ser/trait.CellSerialize.html)
+/// by default. Additional field attributes:
+///
+/// * `#[tlb(data)]`: field is bit-level, i.e. wrap adapter in
+///   [`Data`](https://docs.rs/tlb/latest/tlb/struct.Data.html).
+/// * `#[tlb(ref)]`: store field in a child cell, i.e. wrap adapter in
+///   [`Ref`](https://docs.rs/tlb/latest/tlb/struct.Ref.html).
+///
+/// These are applied in the order above, so `#[tlb(ref, data, as = "NBits<24>")]`
+/// is equivalent to `#[tlb(as = "Ref<Data<NBits<24>>>")]`.
+/// `Maybe` and `Either` fields are supported with [`Option`] and
+/// [`Either`](https://docs.rs/either/latest/either/enum.Either.html) adapters,
+/// e.g. `#[tlb(as = "Option<Ref>")]` for `(Maybe ^X)`.
+///
+/// ```rust
+/// # use tlb::{
+/// #   bits::VarInt,
+/// #   de::CellDeserialize,
+/// #   ser::{CellSerialize, CellSerializeExt},
+/// #   Cell, StringError,
+/// # };
+/// # use num_bigint::BigUint;
+/// /// ```tlb
+/// /// transfer#0f8a7ea5 query_id:uint64 amount:(VarUInteger 16)
+/// ///     custom_payload:(Maybe ^Cell) forward_payload:(Either Cell ^Cell)
+/// ///     = Transfer;
+/// /// ```
+/// #[derive(Debug, PartialEq, CellSerialize, CellDeserialize)]
+/// #[tlb(tag = 0x0f8a7ea5)]
+/// struct Transfer {
+///     #[tlb(data)]
+///     query_id: u64,
+///     #[tlb(data, as = "VarInt<4>")]
+///     amount: BigUint,
+///     #[tlb(as = "Option<Ref>")]
+///     custom_payload: Option<Cell>,
+///     #[tlb(as = "EitherInlineOrRef")]
+///     forward_payload: Cell,
+/// }
+/// # use tlb::{EitherInlineOrRef, Ref};
+/// # fn main() -> Result<(), StringError> {
+/// # let transfer = Transfer {
+/// #     query_id: 1,
+/// #     amount: 2u8.into(),
+/// #     custom_payload: None,
+/// #     forward_payload: Cell::default(),
+/// # };
+/// # let cell = transfer.to_cell(())?;
+/// # assert_eq!(cell.parse_fully::<Transfer>(())?, transfer);
+/// # Ok(())
+/// # }
+/// ```
+#[proc_macro_derive(CellDeserialize, attributes(tlb))]
+pub fn derive_cell_deserialize(input: TokenStream) -> TokenStream {
+    unpack::expand(parse_macro_input!(input as DeriveInput), Flavor::Cell)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,146 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, parse_quote};
+
+use crate::{
+    container::{Container, Field, Kind, Variant},
+    flavor::Flavor,
+};
+
+pub fn expand(input: DeriveInput, flavor: Flavor) -> syn::Result<TokenStream> {
+    let c = Container::from_ast(input, flavor)?;
+    let krate = &c.attrs.crate_path;
+    let bits = flavor.bits(krate);
+    let ser_trait = flavor.ser_trait(krate);
+
+    let generics = c.with_bounds(c.generics.clone(), |f| {
+        let ty = &f.ty;
+        let (bound, args) = match &f.adapter {
+            Some(r#as) => {
+                let as_trait = flavor.ser_as_trait(krate, ty);
+                (
+                    parse_quote!(#r#as: #as_trait),
+                    parse_quote!(<#r#as as #as_trait>::Args: #bits::NoArgs),
+                )
+            }
+            None => (
+                parse_quote!(#ty: #ser_trait),
+                parse_quote!(<#ty as #ser_trait>::Args: #bits::NoArgs),
+            ),
+        };
+        if f.attrs.args.is_some() {
+            vec![bound]
+        } else {
+            vec![bound, args]
+        }
+    });
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = c.generics.split_for_impl();
+    let ident = &c.ident;
+    let (args_ty, args_pat) = c.args();
+
+    let arms = c.variants().iter().map(|v| {
+        let pat = v.destructure();
+        let tag = pack_tag(&c, v);
+        let fields = v.fields.iter().map(|f| pack_field(&c, f));
+        quote! {
+            #pat => {
+                #tag
+                #(#fields)*
+            }
+        }
+    });
+    let body = match &c.kind {
+        Kind::Enum(variants) if variants.is_empty() => quote!(match *self {}),
+        _ => quote! {
+            match self {
+                #(#arms)*
+            }
+            ::core::result::Result::Ok(())
+        },
+    };
+
+    let method = match flavor {
+        Flavor::Bits => quote! {
+            fn pack<W>(&self, writer: &mut W, #args_pat: Self::Args) -> ::core::result::Result<(), W::Error>
+            where
+                W: #krate::ser::BitWriter + ?::core::marker::Sized,
+            {
+                #body
+            }
+        },
+        Flavor::Cell => quote! {
+            fn store(
+                &self,
+                builder: &mut #krate::ser::CellBuilder,
+                #args_pat: Self::Args,
+            ) -> ::core::result::Result<(), #krate::ser::CellBuilderError> {
+                #body
+            }
+        },
+    };
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics #ser_trait for #ident #ty_generics #where_clause {
+            type Args = #args_ty;
+
+            #[inline]
+            #[allow(unused_variables)]
+            #method
+        }
+    })
+}
+
+/// Identifier of the writer argument
+fn writer(c: &Container) -> TokenStream {
+    match c.flavor {
+        Flavor::Bits => quote!(writer),
+        Flavor::Cell => quote!(builder),
+    }
+}
+
+fn pack_tag(c: &Container, v: &Variant) -> TokenStream {
+    let krate = &c.attrs.crate_path;
+    let bits = c.flavor.bits(krate);
+    let writer = writer(c);
+    let Some(tag) = v.tag else {
+        return TokenStream::new();
+    };
+    let (value, len) = (tag.value, tag.len);
+    quote! {
+        #bits::Context::context(
+            <#bits::NBits<#len> as #bits::ser::BitPackAs<u128>>::pack_as(&#value, #writer, ()),
+            "tag",
+        )?;
+    }
+}
+
+fn pack_field(c: &Container, f: &Field) -> TokenStream {
+    let krate = &c.attrs.crate_path;
+    let bits = c.flavor.bits(krate);
+    let writer = writer(c);
+    let (ty, binding, name) = (&f.ty, &f.binding, f.name());
+    let args = f
+        .attrs
+        .args
+        .as_ref()
+        .map_or_else(|| quote!(#bits::NoArgs::EMPTY), |args| quote!(#args));
+    let pack = match (&f.adapter, c.flavor) {
+        (Some(r#as), Flavor::Bits) => quote! {
+            <#r#as as #krate::ser::BitPackAs<#ty>>::pack_as(#binding, #writer, #args)
+        },
+        (Some(r#as), Flavor::Cell) => quote! {
+            <#r#as as #krate::ser::CellSerializeAs<#ty>>::store_as(#binding, #writer, #args)
+        },
+        (None, Flavor::Bits) => quote! {
+            <#ty as #krate::ser::BitPack>::pack(#binding, #writer, #args)
+        },
+        (None, Flavor::Cell) => quote! {
+            <#ty as #krate::ser::CellSerialize>::store(#binding, #writer, #args)
+        },
+    };
+    quote! {
+        #bits::Context::context(#pack, #name)?;
+    }
+}
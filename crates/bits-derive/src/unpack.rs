@@ -0,0 +1,233 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, Lifetime, parse_quote};
+
+use crate::{
+    container::{Container, Field, Kind, Variant},
+    flavor::Flavor,
+};
+
+pub fn expand(input: DeriveInput, flavor: Flavor) -> syn::Result<TokenStream> {
+    let c = Container::from_ast(input, flavor)?;
+    let krate = &c.attrs.crate_path;
+    let bits = flavor.bits(krate);
+
+    let (de_generics, de) = c.de_generics();
+    let de_trait = flavor.de_trait(krate, &de);
+    let generics = c.with_bounds(de_generics, |f| {
+        let ty = &f.ty;
+        let (bound, args) = match &f.adapter {
+            Some(r#as) => {
+                let as_trait = flavor.de_as_trait(krate, &de, ty);
+                (
+                    parse_quote!(#r#as: #as_trait),
+                    parse_quote!(<#r#as as #as_trait>::Args: #bits::NoArgs),
+                )
+            }
+            None => (
+                parse_quote!(#ty: #de_trait),
+                parse_quote!(<#ty as #de_trait>::Args: #bits::NoArgs),
+            ),
+        };
+        if f.attrs.args.is_some() {
+            vec![bound]
+        } else {
+            vec![bound, args]
+        }
+    });
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = c.generics.split_for_impl();
+    let ident = &c.ident;
+    let (args_ty, args_pat) = c.args();
+    let reader = reader(&c);
+    let error = match flavor {
+        Flavor::Bits => quote!(R::Error),
+        Flavor::Cell => quote!(#krate::de::CellParserError<#de>),
+    };
+
+    let body = match &c.kind {
+        Kind::Struct(v) => {
+            let tag = v.tag.map(|tag| {
+                let (value, len) = (tag.value, tag.len);
+                let format = if len % 4 == 0 {
+                    format!("unknown tag: {{:#0{w}x}}, expected: {{:#0{w}x}}", w = len / 4 + 2)
+                } else {
+                    format!("unknown tag: {{:#0{w}b}}, expected: {{:#0{w}b}}", w = len + 2)
+                };
+                quote! {
+                    let __tag = #bits::Context::context(
+                        <#bits::NBits<#len> as #bits::de::BitUnpackAs<#de, u128>>::unpack_as(#reader, ()),
+                        "tag",
+                    )?;
+                    if __tag != #value {
+                        return ::core::result::Result::Err(<#error as #bits::Error>::custom(
+                            ::std::format!(#format, __tag, #value),
+                        ));
+                    }
+                }
+            });
+            let construct = unpack_variant(&c, v, &de);
+            quote! {
+                #tag
+                ::core::result::Result::Ok({ #construct })
+            }
+        }
+        Kind::Enum(variants) if variants.is_empty() => quote! {
+            ::core::result::Result::Err(<#error as #bits::Error>::custom(
+                ::std::concat!(::std::stringify!(#ident), " has no variants"),
+            ))
+        },
+        Kind::Enum(variants) => {
+            let tags: Vec<_> = variants
+                .iter()
+                .map(|v| v.tag.expect("enum variants are always tagged"))
+                .collect();
+            let max_len = tags.iter().map(|t| t.len).max().unwrap_or_default();
+            if tags.iter().all(|t| t.len == max_len) {
+                // all tags are of the same length, so read it at once
+                let arms = variants.iter().zip(&tags).map(|(v, tag)| {
+                    let value = tag.value;
+                    let construct = unpack_variant(&c, v, &de);
+                    quote! {
+                        #value => { #construct }
+                    }
+                });
+                let unknown = unknown_prefix(quote!(__tag), quote!(#max_len));
+                quote! {
+                    let __tag = #bits::Context::context(
+                        <#bits::NBits<#max_len> as #bits::de::BitUnpackAs<#de, u128>>::unpack_as(#reader, ()),
+                        "tag",
+                    )?;
+                    ::core::result::Result::Ok(match __tag {
+                        #(#arms)*
+                        _ => {
+                            return ::core::result::Result::Err(<#error as #bits::Error>::custom(
+                                #unknown,
+                            ));
+                        }
+                    })
+                }
+            } else {
+                // prefix codes of different lengths are read bit by bit
+                let arms = variants.iter().zip(&tags).map(|(v, tag)| {
+                    let (value, len) = (tag.value, tag.len);
+                    let construct = unpack_variant(&c, v, &de);
+                    quote! {
+                        (#len, #value) => break { #construct },
+                    }
+                });
+                let unknown = unknown_prefix(quote!(__tag), quote!(__len));
+                quote! {
+                    let mut __tag: u128 = 0;
+                    let mut __len: usize = 0;
+                    ::core::result::Result::Ok(loop {
+                        let __bit = #bits::Context::context(
+                            <bool as #bits::de::BitUnpack<#de>>::unpack(#reader, ()),
+                            "tag",
+                        )?;
+                        __tag = (__tag << 1) | __bit as u128;
+                        __len += 1;
+                        match (__len, __tag) {
+                            #(#arms)*
+                            _ if __len >= #max_len => {
+                                return ::core::result::Result::Err(<#error as #bits::Error>::custom(
+                                    #unknown,
+                                ));
+                            }
+                            _ => {}
+                        }
+                    })
+                }
+            }
+        }
+    };
+
+    let method = match flavor {
+        Flavor::Bits => quote! {
+            fn unpack<R>(reader: &mut R, #args_pat: Self::Args) -> ::core::result::Result<Self, R::Error>
+            where
+                R: #krate::de::BitReader<#de> + ?::core::marker::Sized,
+            {
+                #body
+            }
+        },
+        Flavor::Cell => quote! {
+            fn parse(
+                parser: &mut #krate::de::CellParser<#de>,
+                #args_pat: Self::Args,
+            ) -> ::core::result::Result<Self, #error> {
+                #body
+            }
+        },
+    };
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics #de_trait for #ident #ty_generics #where_clause {
+            type Args = #args_ty;
+
+            #[inline]
+            #[allow(unused_variables)]
+            #method
+        }
+    })
+}
+
+/// Error message for unknown enum tag of given length in bits: hex when
+/// it consists of whole hex digits, binary otherwise
+fn unknown_prefix(tag: TokenStream, len: TokenStream) -> TokenStream {
+    quote! {
+        if #len % 4 == 0 {
+            ::std::format!("unknown prefix {:#x}", #tag)
+        } else {
+            ::std::format!("unknown prefix {:#0w$b}", #tag, w = #len + 2)
+        }
+    }
+}
+
+/// Identifier of the reader argument
+fn reader(c: &Container) -> TokenStream {
+    match c.flavor {
+        Flavor::Bits => quote!(reader),
+        Flavor::Cell => quote!(parser),
+    }
+}
+
+/// Unpacks all fields of the variant one by one and constructs it
+fn unpack_variant(c: &Container, v: &Variant, de: &Lifetime) -> TokenStream {
+    let fields = v.fields.iter().map(|f| unpack_field(c, f, de));
+    let construct = v.destructure();
+    quote! {
+        #(#fields)*
+        #construct
+    }
+}
+
+fn unpack_field(c: &Container, f: &Field, de: &Lifetime) -> TokenStream {
+    let krate = &c.attrs.crate_path;
+    let bits = c.flavor.bits(krate);
+    let reader = reader(c);
+    let (ty, binding, name) = (&f.ty, &f.binding, f.name());
+    let args = f
+        .attrs
+        .args
+        .as_ref()
+        .map_or_else(|| quote!(#bits::NoArgs::EMPTY), |args| quote!(#args));
+    let unpack = match (&f.adapter, c.flavor) {
+        (Some(r#as), Flavor::Bits) => quote! {
+            <#r#as as #krate::de::BitUnpackAs<#de, #ty>>::unpack_as(#reader, #args)
+        },
+        (Some(r#as), Flavor::Cell) => quote! {
+            <#r#as as #krate::de::CellDeserializeAs<#de, #ty>>::parse_as(#reader, #args)
+        },
+        (None, Flavor::Bits) => quote! {
+            <#ty as #krate::de::BitUnpack<#de>>::unpack(#reader, #args)
+        },
+        (None, Flavor::Cell) => quote! {
+            <#ty as #krate::de::CellDeserialize<#de>>::parse(#reader, #args)
+        },
+    };
+    quote! {
+        let #binding = #bits::Context::context(#unpack, #name)?;
+    }
+}
@@ -0,0 +1,189 @@
+use std::{borrow::Cow, fmt::Debug};
+
+use num_bigint::BigUint;
+use tlbits::{
+    BorrowCow, NBits, NoArgs, VarInt,
+    bitvec::{bits, order::Msb0, slice::BitSlice},
+    de::{BitUnpack, unpack_fully},
+    ser::{BitPack, pack},
+};
+
+#[track_caller]
+fn assert_pack_unpack_eq<T>(value: T, args: <T as BitPack>::Args)
+where
+    T: BitPack + PartialEq + Debug,
+    <T as BitPack>::Args: Clone,
+    for<'de> T: BitUnpack<'de, Args = <T as BitPack>::Args>,
+{
+    let packed = pack(&value, args.clone()).expect("pack");
+    let unpacked: T = unpack_fully(&packed, args).expect("unpack_fully");
+    assert_eq!(unpacked, value)
+}
+
+/// ```tlb
+/// tag$10 query_id:uint64 amount:(VarUInteger 16) = Hello;
+/// ```
+#[derive(Debug, PartialEq, BitPack, BitUnpack)]
+#[bits(tag = "0b10")]
+struct Hello {
+    query_id: u64,
+    #[bits(as = "VarInt<4>")]
+    amount: BigUint,
+}
+
+#[test]
+fn struct_with_tag() {
+    let value = Hello {
+        query_id: 123,
+        amount: 1_000u64.into(),
+    };
+    let packed = pack(&value, ()).unwrap();
+    assert_eq!(packed[..2], bits![u8, Msb0; 1, 0]);
+    assert_pack_unpack_eq(value, ());
+}
+
+#[test]
+fn struct_unknown_tag() {
+    let packed = pack((false, true, 0u64, 0u8), NoArgs::EMPTY).unwrap();
+    let err = unpack_fully::<Hello>(&packed, ()).unwrap_err();
+    assert!(err.to_string().contains("unknown tag"), "{err}");
+}
+
+#[derive(Debug, PartialEq, BitPack, BitUnpack)]
+struct Tuple(#[bits(as = "NBits<7>")] u8, bool);
+
+#[derive(Debug, PartialEq, BitPack, BitUnpack)]
+struct Unit;
+
+#[test]
+fn tuple_and_unit_structs() {
+    assert_eq!(pack(Tuple(3, true), ()).unwrap().len(), 8);
+    assert_pack_unpack_eq(Tuple(3, true), ());
+    assert!(pack(Unit, ()).unwrap().is_empty());
+    assert_pack_unpack_eq(Unit, ());
+}
+
+#[derive(Debug, PartialEq, BitPack, BitUnpack)]
+enum Op {
+    #[bits(tag = "0b0")]
+    Nop,
+    #[bits(tag = "$10")]
+    Set(#[bits(as = "NBits<4>")] u8),
+    #[bits(tag = "0b110")]
+    Add { a: u8, b: u8 },
+    #[bits(tag = "#e")]
+    Flag { flag: bool },
+}
+
+#[test]
+fn enum_prefix_tags() {
+    for (value, len) in [
+        (Op::Nop, 1),
+        (Op::Set(5), 2 + 4),
+        (Op::Add { a: 1, b: 2 }, 3 + 16),
+        (Op::Flag { flag: true }, 4 + 1),
+    ] {
+        assert_eq!(pack(&value, ()).unwrap().len(), len);
+        assert_pack_unpack_eq(value, ());
+    }
+}
+
+#[test]
+fn enum_unknown_tag() {
+    let err = unpack_fully::<Op>(bits![u8, Msb0; 1, 1, 1], ()).unwrap_err();
+    assert!(err.to_string().starts_with("tag"), "{err}");
+
+    let err = unpack_fully::<Op>(bits![u8, Msb0; 1, 1, 1, 1], ()).unwrap_err();
+    assert!(err.to_string().contains("unknown prefix 0xf"), "{err}");
+}
+
+#[derive(Debug, PartialEq, BitPack, BitUnpack)]
+enum Dir {
+    #[bits(tag = "$01")]
+    Left,
+    #[bits(tag = "$10")]
+    Right,
+}
+
+#[test]
+fn enum_same_len_tags() {
+    assert_pack_unpack_eq(Dir::Left, ());
+    assert_pack_unpack_eq(Dir::Right, ());
+
+    let err = unpack_fully::<Dir>(bits![u8, Msb0; 1, 1], ()).unwrap_err();
+    assert_eq!(err.to_string(), "unknown prefix 0b11");
+}
+
+#[derive(Debug, PartialEq, BitPack, BitUnpack)]
+struct Generic<T> {
+    value: T,
+    rest: Option<T>,
+}
+
+#[test]
+fn generic() {
+    assert_pack_unpack_eq(
+        Generic {
+            value: 1u16,
+            rest: Some(2),
+        },
+        (),
+    );
+}
+
+#[derive(Debug, PartialEq, BitPack, BitUnpack)]
+#[bits(args = "len: usize")]
+struct Borrowed<'a> {
+    #[bits(as = "NBits<7>")]
+    len: u8,
+    #[bits(as = "BorrowCow", args = len)]
+    data: Cow<'a, BitSlice<u8, Msb0>>,
+}
+
+#[test]
+fn borrowed() {
+    let data = bits![u8, Msb0; 1, 0, 1, 1, 0];
+    let value = Borrowed {
+        len: 5,
+        data: Cow::Borrowed(data),
+    };
+    let packed = pack(&value, data.len()).unwrap();
+    let unpacked: Borrowed = unpack_fully(&packed, data.len()).unwrap();
+    assert!(matches!(unpacked.data, Cow::Borrowed(_)));
+    assert_eq!(unpacked, value);
+}
+
+#[derive(Debug, PartialEq, BitPack, BitUnpack)]
+struct FieldArgs<'a> {
+    #[bits(as = "BorrowCow", args = 3)]
+    bytes: Cow<'a, [u8]>,
+    #[bits(as = "BorrowCow", args = "2 * 2")]
+    text: Cow<'a, str>,
+}
+
+#[test]
+fn field_args() {
+    let value = FieldArgs {
+        bytes: Cow::Borrowed(&[1, 2, 3]),
+        text: Cow::Borrowed(&"toner"[..4]),
+    };
+    let packed = pack(&value, ()).unwrap();
+    assert_eq!(packed.len(), 7 * 8);
+    assert_eq!(unpack_fully::<FieldArgs>(&packed, ()).unwrap(), value);
+
+    let err = pack(
+        FieldArgs {
+            bytes: Cow::Borrowed(&[1]),
+            ..value
+        },
+        (),
+    )
+    .unwrap_err();
+    assert!(err.to_string().starts_with("bytes"), "{err}");
+}
+
+#[test]
+fn field_context() {
+    let err = unpack_fully::<Hello>(bits![u8, Msb0; 1, 0, 1], ()).unwrap_err();
+    assert!(err.to_string().starts_with("query_id"), "{err}");
+}
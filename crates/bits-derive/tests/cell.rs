@@ -0,0 +1,166 @@
+use core::fmt::Debug;
+
+use num_bigint::BigUint;
+use tlb::{
+    Cell, Data, Ref, Same,
+    bits::{NBits, VarInt, ser::BitWriterExt},
+    de::{CellDeserialize, CellDeserializeOwned},
+    either::Either,
+    ser::{CellSerialize, CellSerializeExt},
+};
+
+#[track_caller]
+fn assert_store_parse_eq<T>(value: T) -> Cell
+where
+    T: CellSerialize<Args = ()> + CellDeserializeOwned + PartialEq + Debug,
+    for<'de> T: CellDeserialize<'de, Args = ()>,
+{
+    let cell = value.to_cell(()).expect("store");
+    let got: T = cell.parse_fully(()).expect("parse_fully");
+    assert_eq!(got, value);
+    cell
+}
+
+/// ```tlb
+/// transfer#0f8a7ea5 query_id:uint64 amount:(VarUInteger 16) destination:^Cell
+///     custom_payload:(Maybe ^Cell) forward_payload:(Either Cell ^Cell) = Transfer;
+/// ```
+#[derive(Debug, PartialEq, CellSerialize, CellDeserialize)]
+#[tlb(tag = 0x0f8a7ea5)]
+struct Transfer {
+    #[tlb(data)]
+    query_id: u64,
+    #[tlb(data, as = "VarInt<4>")]
+    amount: BigUint,
+    #[tlb(ref)]
+    destination: Cell,
+    #[tlb(as = "Option<Ref>")]
+    custom_payload: Option<Cell>,
+    #[tlb(as = "Either<Same, Ref>")]
+    forward_payload: Either<Cell, Cell>,
+}
+
+fn transfer() -> Transfer {
+    Transfer {
+        query_id: 1,
+        amount: 1_000u64.into(),
+        destination: Cell::default(),
+        custom_payload: Some(Cell::default()),
+        forward_payload: Either::Right(Cell::default()),
+    }
+}
+
+#[test]
+fn struct_with_opcode() {
+    let cell = assert_store_parse_eq(transfer());
+    assert_eq!(cell.parser().parse_as::<u32, Data>(()).unwrap(), 0x0f8a7ea5);
+    assert_eq!(cell.references().len(), 3);
+}
+
+#[test]
+fn struct_unknown_opcode() {
+    let mut builder = Cell::builder();
+    builder.pack(0xdeadbeef_u32, ()).unwrap();
+    let err = builder
+        .into_cell()
+        .unwrap()
+        .parse_fully::<Transfer>(())
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("unknown tag: 0xdeadbeef, expected: 0x0f8a7ea5"),
+        "{err}"
+    );
+}
+
+#[test]
+fn field_context() {
+    let mut builder = Cell::builder();
+    builder
+        .pack(0x0f8a7ea5_u32, ())
+        .unwrap()
+        .pack(0u64, ())
+        .unwrap();
+    let err = builder
+        .into_cell()
+        .unwrap()
+        .parse_fully::<Transfer>(())
+        .unwrap_err();
+    assert!(err.to_string().starts_with("amount"), "{err}");
+}
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, PartialEq, CellSerialize, CellDeserialize)]
+enum Action {
+    #[tlb(tag = 0x0ec3c86d)]
+    SendMsg {
+        #[tlb(data)]
+        mode: u8,
+        #[tlb(ref)]
+        message: Transfer,
+    },
+    #[tlb(tag = 0xad4de08e)]
+    SetCode(#[tlb(ref, context = "action_set_code")] Cell),
+    #[tlb(tag = 0x26fa1dd4)]
+    ChangeLibrary(#[tlb(ref, data, as = "NBits<7>")] u8),
+}
+
+#[test]
+fn enum_with_opcodes() {
+    assert_store_parse_eq(Action::SendMsg {
+        mode: 3,
+        message: transfer(),
+    });
+    assert_store_parse_eq(Action::SetCode(Cell::default()));
+    let cell = assert_store_parse_eq(Action::ChangeLibrary(5));
+    assert_eq!(cell.references()[0].data().len(), 7);
+}
+
+#[test]
+fn enum_unknown_opcode() {
+    let mut builder = Cell::builder();
+    builder.pack(0xdeadbeef_u32, ()).unwrap();
+    let err = builder
+        .into_cell()
+        .unwrap()
+        .parse_fully::<Action>(())
+        .unwrap_err();
+    assert_eq!(err.to_string(), "unknown prefix 0xdeadbeef");
+
+    // opcode is read at once
+    let mut builder = Cell::builder();
+    builder.pack(0x0ec3_u16, ()).unwrap();
+    let err = builder
+        .into_cell()
+        .unwrap()
+        .parse_fully::<Action>(())
+        .unwrap_err();
+    assert!(err.to_string().starts_with("tag"), "{err}");
+}
+
+#[derive(Debug, PartialEq, CellSerialize, CellDeserialize)]
+struct Generic<T> {
+    #[tlb(ref)]
+    value: T,
+    maybe: Option<T>,
+}
+
+#[test]
+fn variant_field_context() {
+    let mut builder = Cell::builder();
+    builder.pack(0xad4de08e_u32, ()).unwrap();
+    let err = builder
+        .into_cell()
+        .unwrap()
+        .parse_fully::<Action>(())
+        .unwrap_err();
+    assert!(err.to_string().starts_with("action_set_code"), "{err}");
+}
+
+#[test]
+fn generic() {
+    assert_store_parse_eq(Generic {
+        value: Action::SetCode(Cell::default()),
+        maybe: None,
+    });
+}
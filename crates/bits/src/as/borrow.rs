@@ -1,15 +1,78 @@
 use std::borrow::Cow;
 
-use bitvec::{mem::bits_of, order::Msb0, slice::BitSlice};
+use bitvec::{mem::bits_of, order::Msb0, slice::BitSlice, view::AsBits};
 
 use crate::{
     Error,
     de::{BitReader, BitReaderExt, BitUnpackAs},
+    ser::{BitPackAs, BitWriter},
 };
 
+/// Adapter to **de**serialize [`Cow`] borrowing from the reader, when possible.
+///
+/// Args are the length of data, which is also checked on **ser**ialization.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct BorrowCow;
 
+#[inline]
+fn ensure_len<E>(len: usize, expected: usize) -> Result<(), E>
+where
+    E: Error,
+{
+    if len != expected {
+        return Err(Error::custom(format!(
+            "length mismatch: expected {expected}, got {len}"
+        )));
+    }
+    Ok(())
+}
+
+impl BitPackAs<Cow<'_, BitSlice<u8, Msb0>>> for BorrowCow {
+    /// length in bits
+    type Args = usize;
+
+    #[inline]
+    fn pack_as<W>(
+        source: &Cow<'_, BitSlice<u8, Msb0>>,
+        writer: &mut W,
+        len: Self::Args,
+    ) -> Result<(), W::Error>
+    where
+        W: BitWriter + ?Sized,
+    {
+        ensure_len(source.len(), len)?;
+        writer.write_bitslice(source)
+    }
+}
+
+impl BitPackAs<Cow<'_, [u8]>> for BorrowCow {
+    /// length in bytes
+    type Args = usize;
+
+    #[inline]
+    fn pack_as<W>(source: &Cow<'_, [u8]>, writer: &mut W, len: Self::Args) -> Result<(), W::Error>
+    where
+        W: BitWriter + ?Sized,
+    {
+        ensure_len(source.len(), len)?;
+        writer.write_bitslice(source.as_bits())
+    }
+}
+
+impl BitPackAs<Cow<'_, str>> for BorrowCow {
+    /// length in bytes
+    type Args = usize;
+
+    #[inline]
+    fn pack_as<W>(source: &Cow<'_, str>, writer: &mut W, len: Self::Args) -> Result<(), W::Error>
+    where
+        W: BitWriter + ?Sized,
+    {
+        ensure_len(source.len(), len)?;
+        writer.write_bitslice(source.as_bytes().as_bits())
+    }
+}
+
 impl<'de: 'a, 'a> BitUnpackAs<'de, Cow<'a, BitSlice<u8, Msb0>>> for BorrowCow {
     /// length in bits
     type Args = usize;
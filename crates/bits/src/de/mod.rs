@@ -4,6 +4,10 @@ mod reader;
 
 pub use self::{r#as::*, reader::*};
 
+/// Derive [`BitUnpack`] implementation, requires `derive` feature
+#[cfg(feature = "derive")]
+pub use tlbits_derive::BitUnpack;
+
 use std::{
     borrow::Cow,
     collections::{BTreeMap, BTreeSet, HashMap, HashSet, LinkedList, VecDeque},
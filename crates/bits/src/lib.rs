@@ -137,8 +137,9 @@ pub mod de;
 mod error;
 pub mod integer;
 pub mod ser;
+pub mod string;
 
-pub use self::{r#as::*, error::*};
+pub use self::{r#as::*, error::*, string::BitString};
 
 pub use bitvec;
 pub use either;
@@ -4,6 +4,10 @@ mod writer;
 
 pub use self::{r#as::*, writer::*};
 
+/// Derive [`BitPack`] implementation, requires `derive` feature
+#[cfg(feature = "derive")]
+pub use tlbits_derive::BitPack;
+
 use std::{borrow::Cow, rc::Rc, sync::Arc};
 
 use bitvec::{array::BitArray, order::Msb0, slice::BitSlice, vec::BitVec, view::BitViewSized};
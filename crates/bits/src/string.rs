@@ -0,0 +1,418 @@
+//! TL-B and Fift notation for bitstrings
+use core::{
+    fmt::{self, Binary, Debug, Display, Write},
+    ops::{Deref, DerefMut},
+    str::FromStr,
+};
+
+use bitvec::{order::Msb0, slice::BitSlice, vec::BitVec, view::BitView};
+
+use crate::{
+    Error, StringError,
+    de::{BitReader, BitUnpack},
+    ser::{BitPack, BitWriter},
+};
+
+/// Owned bitstring, which can be written and parsed in TL-B and Fift
+/// notations:
+/// * `x{D_}`: hex, where incomplete trailing digit is padded with
+///   completion tag (a single `1` bit followed by `0`s) and marked with
+///   `_`
+/// * `b{1101}`: binary
+/// * `#D_` and `$1101`: same as above, as used in TL-B constructor tags,
+///   where `#_` and `$_` stand for empty bitstring
+///
+/// Surrounding ASCII whitespace is ignored when parsing.
+///
+/// [`Display`] uses hex notation, while [`Binary`] uses binary one:
+/// ```rust
+/// # use tlbits::{BitString, StringError};
+/// # fn main() -> Result<(), StringError> {
+/// let s: BitString = "b{1101}".parse()?;
+/// assert_eq!(s.len(), 4);
+/// assert_eq!(s.to_string(), "x{D}");
+/// assert_eq!("$110".parse::<BitString>()?.to_string(), "x{D_}");
+/// assert_eq!(format!("{:b}", "#D_".parse::<BitString>()?), "b{110}");
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Literals can be validated at compile-time with [`bits!`](crate::bits!).
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BitString(BitVec<u8, Msb0>);
+
+impl BitString {
+    /// Create empty bitstring
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self(BitVec::EMPTY)
+    }
+
+    #[inline]
+    pub fn into_bitvec(self) -> BitVec<u8, Msb0> {
+        self.0
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn __from_literal((bytes, len): &([u8; LITERAL_BYTES], usize)) -> Self {
+        Self(bytes.view_bits::<Msb0>()[..*len].to_bitvec())
+    }
+}
+
+impl Deref for BitString {
+    type Target = BitVec<u8, Msb0>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for BitString {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl AsRef<BitSlice<u8, Msb0>> for BitString {
+    #[inline]
+    fn as_ref(&self) -> &BitSlice<u8, Msb0> {
+        &self.0
+    }
+}
+
+impl From<BitVec<u8, Msb0>> for BitString {
+    #[inline]
+    fn from(bits: BitVec<u8, Msb0>) -> Self {
+        Self(bits)
+    }
+}
+
+impl From<&BitSlice<u8, Msb0>> for BitString {
+    #[inline]
+    fn from(bits: &BitSlice<u8, Msb0>) -> Self {
+        Self(bits.to_bitvec())
+    }
+}
+
+impl From<BitString> for BitVec<u8, Msb0> {
+    #[inline]
+    fn from(s: BitString) -> Self {
+        s.0
+    }
+}
+
+impl FromIterator<bool> for BitString {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl Display for BitString {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "x{{{}}}", hex(&self.0))
+    }
+}
+
+impl Binary for BitString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("b{")?;
+        for bit in self.0.iter().by_vals() {
+            f.write_char(if bit { '1' } else { '0' })?;
+        }
+        f.write_char('}')
+    }
+}
+
+impl Debug for BitString {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl FromStr for BitString {
+    type Err = StringError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim_ascii();
+        if let Some(digits) = s.strip_prefix("x{").and_then(|s| s.strip_suffix('}')) {
+            return parse_hex(digits).map(Self);
+        }
+        if let Some(digits) = s.strip_prefix("b{").and_then(|s| s.strip_suffix('}')) {
+            return parse_binary(digits).map(Self);
+        }
+        match s.split_at_checked(1) {
+            Some(("#" | "$", "_")) => Ok(Self::new()),
+            Some(("#", digits)) => parse_hex(digits).map(Self),
+            Some(("$", digits)) => parse_binary(digits).map(Self),
+            _ => Err(Error::custom(format!(
+                "expected x{{...}}, b{{...}}, #... or $..., got: {s:?}"
+            ))),
+        }
+    }
+}
+
+impl BitPack for BitString {
+    type Args = ();
+
+    #[inline]
+    fn pack<W>(&self, writer: &mut W, _: Self::Args) -> Result<(), W::Error>
+    where
+        W: BitWriter + ?Sized,
+    {
+        writer.write_bitslice(&self.0)
+    }
+}
+
+impl<'de> BitUnpack<'de> for BitString {
+    /// length in bits
+    type Args = usize;
+
+    #[inline]
+    fn unpack<R>(reader: &mut R, len: Self::Args) -> Result<Self, R::Error>
+    where
+        R: BitReader<'de> + ?Sized,
+    {
+        BitVec::unpack(reader, len).map(Self)
+    }
+}
+
+/// Format bits as hex digits with completion tag, i.e. contents of
+/// `x{...}` of [`BitString`]
+#[inline]
+pub fn hex(bits: &BitSlice<u8, Msb0>) -> impl Display + '_ {
+    Hex(bits)
+}
+
+struct Hex<'a>(&'a BitSlice<u8, Msb0>);
+
+impl Display for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut chunks = self.0.chunks_exact(4);
+        for digit in &mut chunks {
+            write_digit(f, load_digit(digit))?;
+        }
+        let rest = chunks.remainder();
+        if !rest.is_empty() {
+            // completion tag
+            let digit = (load_digit(rest) << 1 | 1) << (3 - rest.len());
+            write_digit(f, digit)?;
+            f.write_char('_')?;
+        }
+        Ok(())
+    }
+}
+
+#[inline]
+fn load_digit(bits: &BitSlice<u8, Msb0>) -> u8 {
+    bits.iter().fold(0, |digit, bit| digit << 1 | *bit as u8)
+}
+
+#[inline]
+fn write_digit(f: &mut fmt::Formatter<'_>, digit: u8) -> fmt::Result {
+    f.write_char(char::from_digit(digit as u32, 16).map_or('?', |c| c.to_ascii_uppercase()))
+}
+
+/// Parse hex digits with optional completion tag, i.e. contents of
+/// `x{...}` of [`BitString`]
+pub fn parse_hex(s: &str) -> Result<BitVec<u8, Msb0>, StringError> {
+    let (digits, tagged) = match s.strip_suffix('_') {
+        Some(digits) => (digits, true),
+        None => (s, false),
+    };
+    let mut bits = BitVec::with_capacity(digits.len() * 4);
+    for c in digits.chars() {
+        let digit = c
+            .to_digit(16)
+            .ok_or_else(|| Error::custom(format!("invalid hex digit: {c:?}")))?;
+        bits.extend_from_bitslice(&(digit as u8).view_bits::<Msb0>()[4..]);
+    }
+    if tagged {
+        // strip completion tag
+        while !bits
+            .pop()
+            .ok_or_else(|| Error::custom("missing completion tag"))?
+        {}
+    }
+    Ok(bits)
+}
+
+/// Parse binary digits, i.e. contents of `b{...}` of [`BitString`]
+pub fn parse_binary(s: &str) -> Result<BitVec<u8, Msb0>, StringError> {
+    s.chars()
+        .map(|c| match c {
+            '0' => Ok(false),
+            '1' => Ok(true),
+            _ => Err(Error::custom(format!("invalid binary digit: {c:?}"))),
+        })
+        .collect()
+}
+
+/// Max length in bytes of [`bits!`](crate::bits!) literals
+#[doc(hidden)]
+pub const LITERAL_BYTES: usize = 128;
+
+/// Compile-time counterpart of [`BitString::from_str()`], which panics
+/// on invalid input
+#[doc(hidden)]
+pub const fn __parse_literal(s: &str) -> ([u8; LITERAL_BYTES], usize) {
+    let s = s.as_bytes().trim_ascii();
+    let (mut start, end, is_hex) = match s {
+        [b'x', b'{', .., b'}'] => (2, s.len() - 1, true),
+        [b'b', b'{', .., b'}'] => (2, s.len() - 1, false),
+        [b'#', b'_'] | [b'$', b'_'] => (2, 2, false),
+        [b'#', ..] => (1, s.len(), true),
+        [b'$', ..] => (1, s.len(), false),
+        _ => panic!("expected x{{...}}, b{{...}}, #... or $..."),
+    };
+
+    let mut bytes = [0; LITERAL_BYTES];
+    let mut len = 0;
+    let mut tagged = false;
+    while start < end {
+        let c = s[start];
+        start += 1;
+        let (digit, width) = match (is_hex, c) {
+            (true, b'_') if start == end => {
+                tagged = true;
+                break;
+            }
+            (true, b'0'..=b'9') => (c - b'0', 4),
+            (true, b'a'..=b'f') => (c - b'a' + 10, 4),
+            (true, b'A'..=b'F') => (c - b'A' + 10, 4),
+            (true, _) => panic!("invalid hex digit"),
+            (false, b'0' | b'1') => (c - b'0', 1),
+            (false, _) => panic!("invalid binary digit"),
+        };
+        if len + width > LITERAL_BYTES * 8 {
+            panic!("literal is too long");
+        }
+        let mut i = width;
+        while i > 0 {
+            i -= 1;
+            if digit >> i & 1 != 0 {
+                bytes[len / 8] |= 0x80 >> (len % 8);
+            }
+            len += 1;
+        }
+    }
+
+    if tagged {
+        // strip completion tag
+        loop {
+            if len == 0 {
+                panic!("missing completion tag");
+            }
+            len -= 1;
+            let mask = 0x80 >> (len % 8);
+            if bytes[len / 8] & mask != 0 {
+                bytes[len / 8] &= !mask;
+                break;
+            }
+        }
+    }
+    (bytes, len)
+}
+
+/// [`BitString`] literal in TL-B or Fift notation, which is validated at
+/// compile-time:
+/// ```rust
+/// # use tlbits::{bits, BitString};
+/// assert_eq!(bits!("x{D_}"), "b{110}".parse::<BitString>().unwrap());
+/// assert_eq!(bits!("$_"), BitString::new());
+/// ```
+/// Invalid literals fail to compile:
+/// ```compile_fail
+/// # use tlbits::bits;
+/// let _ = bits!("x{G}");
+/// ```
+#[macro_export]
+macro_rules! bits {
+    ($s:literal) => {{
+        const LITERAL: ([u8; $crate::string::LITERAL_BYTES], usize) =
+            $crate::string::__parse_literal($s);
+        $crate::string::BitString::__from_literal(&LITERAL)
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{de::unpack_fully, ser::pack};
+
+    use super::*;
+
+    #[test]
+    fn notations() {
+        for (s, bits) in [
+            ("x{}", ""),
+            ("x{C_}", "1"),
+            ("x{B_}", "101"),
+            ("x{A}", "1010"),
+            ("x{1_}", "000"),
+            ("x{FFC_}", "111111111"),
+            ("b{0110}", "0110"),
+            ("#D_", "110"),
+            ("$1011", "1011"),
+            ("$_", ""),
+            ("#_", ""),
+        ] {
+            let bits: BitString = bits.chars().map(|c| c == '1').collect();
+            assert_eq!(s.parse::<BitString>().unwrap(), bits, "{s}");
+            assert_eq!(BitString::__from_literal(&__parse_literal(s)), bits, "{s}");
+            assert_eq!(
+                bits.to_string().parse::<BitString>().unwrap(),
+                bits,
+                "{bits}"
+            );
+            assert_eq!(
+                format!("{bits:b}").parse::<BitString>().unwrap(),
+                bits,
+                "{bits:b}"
+            );
+        }
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(bits!("$101").to_string(), "x{B_}");
+        assert_eq!(bits!("x{8_}").to_string(), "x{}");
+        assert_eq!(format!("{:b}", bits!("x{B_}")), "b{101}");
+    }
+
+    #[test]
+    fn invalid() {
+        for s in ["x{0_}", "x{G}", "b{2}", "x{A", "A", "", "A_", "9_", "x_"] {
+            assert!(s.parse::<BitString>().is_err(), "{s}");
+        }
+    }
+
+    #[test]
+    fn whitespace() {
+        for s in [" x{D_}", "x{D_}\n", "\t#D_ ", " $110 "] {
+            let bits = bits!("$110");
+            assert_eq!(s.parse::<BitString>().unwrap(), bits, "{s:?}");
+            assert_eq!(
+                BitString::__from_literal(&__parse_literal(s)),
+                bits,
+                "{s:?}"
+            );
+        }
+        assert_eq!(bits!(" x{D_} "), bits!("$110"));
+        assert!("x{ D_}".parse::<BitString>().is_err());
+        assert!(std::panic::catch_unwind(|| __parse_literal("x{ D_}")).is_err());
+    }
+
+    #[test]
+    fn pack_unpack() {
+        let s = bits!("x{ABC_}");
+        let packed = pack(&s, ()).unwrap();
+        assert_eq!(packed, *s);
+        assert_eq!(unpack_fully::<BitString>(&packed, s.len()).unwrap(), s);
+    }
+}
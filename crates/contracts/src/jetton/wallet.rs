@@ -21,80 +21,25 @@ use tlb_ton::{
 /// = InternalMsgBody;
 /// ```
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, CellSerialize, CellDeserialize)]
+#[tlb(crate = "::tlb_ton", tag = 0x0f8a7ea5)]
 pub struct JettonTransfer<P = Cell, F = Cell> {
+    #[tlb(data)]
     pub query_id: u64,
+    #[tlb(data, as = "VarInt<4>")]
     pub amount: BigUint,
+    #[tlb(data)]
     pub dst: MsgAddress,
+    #[tlb(data)]
     pub response_dst: MsgAddress,
+    #[tlb(as = "Option<Ref<ParseFully>>")]
     pub custom_payload: Option<P>,
+    #[tlb(data, as = "VarInt<4>")]
     pub forward_ton_amount: BigUint,
+    #[tlb(as = "EitherInlineOrRef<ParseFully>")]
     pub forward_payload: ForwardPayload<F>,
 }
 
-const JETTON_TRANSFER_TAG: u32 = 0x0f8a7ea5;
-
-impl<P, F> CellSerialize for JettonTransfer<P, F>
-where
-    P: CellSerialize<Args: NoArgs>,
-    F: CellSerialize<Args: NoArgs>,
-{
-    type Args = ();
-
-    fn store(&self, builder: &mut CellBuilder, _: Self::Args) -> Result<(), CellBuilderError> {
-        builder
-            // transfer#0f8a7ea5
-            .pack(JETTON_TRANSFER_TAG, ())?
-            // query_id:uint64
-            .pack(self.query_id, ())?
-            // amount:(VarUInteger 16)
-            .pack_as::<_, &VarInt<4>>(&self.amount, ())?
-            // destination:MsgAddress
-            .pack(self.dst, ())?
-            // response_destination:MsgAddress
-            .pack(self.response_dst, ())?
-            // custom_payload:(Maybe ^Cell)
-            .store_as::<_, Option<Ref>>(self.custom_payload.as_ref(), NoArgs::EMPTY)?
-            // forward_ton_amount:(VarUInteger 16)
-            .pack_as::<_, &VarInt<4>>(&self.forward_ton_amount, ())?
-            // forward_payload:(Either Cell ^Cell)
-            .store_as::<_, EitherInlineOrRef>(&self.forward_payload, NoArgs::EMPTY)?;
-
-        Ok(())
-    }
-}
-
-impl<'de, P, F> CellDeserialize<'de> for JettonTransfer<P, F>
-where
-    P: CellDeserialize<'de, Args: NoArgs>,
-    F: CellDeserialize<'de, Args: NoArgs>,
-{
-    type Args = ();
-
-    fn parse(parser: &mut CellParser<'de>, _: Self::Args) -> Result<Self, CellParserError<'de>> {
-        // transfer#0f8a7ea5
-        parser.unpack::<ConstU32<JETTON_TRANSFER_TAG>>(())?;
-        Ok(Self {
-            // query_id:uint64
-            query_id: parser.unpack(())?,
-            // amount:(VarUInteger 16)
-            amount: parser.unpack_as::<_, VarInt<4>>(())?,
-            // destination:MsgAddress
-            dst: parser.unpack(())?,
-            // response_destination:MsgAddress
-            response_dst: parser.unpack(())?,
-            // custom_payload:(Maybe ^Cell)
-            custom_payload: parser.parse_as::<_, Option<Ref<ParseFully>>>(P::Args::EMPTY)?,
-            // forward_ton_amount:(VarUInteger 16)
-            forward_ton_amount: parser.unpack_as::<_, VarInt<4>>(())?,
-            // forward_payload:(Either Cell ^Cell)
-            forward_payload: parser
-                .parse_as::<Either<ForwardPayload<F>, ForwardPayload<F>>, Either<ParseFully, Ref<ParseFully>>>(NoArgs::EMPTY)?
-                .into_inner(),
-        })
-    }
-}
-
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ForwardPayload<T = Cell> {
@@ -252,45 +197,36 @@ where
 /// = InternalMsgBody;
 /// ```
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, CellSerialize, CellDeserialize)]
+#[tlb(crate = "::tlb_ton", tag = 0x595f07bc)]
 pub struct JettonBurn<P = Cell> {
+    #[tlb(data)]
     pub query_id: u64,
+    #[tlb(data, as = "VarInt<4>")]
     pub amount: BigUint,
+    #[tlb(data)]
     pub response_dst: MsgAddress,
+    #[tlb(as = "Option<Ref<ParseFully>>")]
     pub custom_payload: Option<P>,
 }
 
-const JETTON_BURN_TAG: u32 = 0x595f07bc;
+#[cfg(test)]
+mod tests {
+    use tlb_ton::ser::CellSerializeExt;
 
-impl<P> CellSerialize for JettonBurn<P>
-where
-    P: CellSerialize<Args: NoArgs>,
-{
-    type Args = ();
+    use super::*;
 
-    fn store(&self, builder: &mut CellBuilder, _: Self::Args) -> Result<(), CellBuilderError> {
-        builder
-            .pack(JETTON_BURN_TAG, ())?
-            .pack_as::<_, &VarInt<4>>(&self.amount, ())?
-            .pack(self.response_dst, ())?
-            .store_as::<_, Option<Ref>>(self.custom_payload.as_ref(), NoArgs::EMPTY)?;
-        Ok(())
-    }
-}
-
-impl<'de, P> CellDeserialize<'de> for JettonBurn<P>
-where
-    P: CellDeserialize<'de, Args: NoArgs>,
-{
-    type Args = ();
+    #[test]
+    fn burn_body() {
+        let burn = JettonBurn::<Cell> {
+            query_id: 1,
+            amount: 1000u32.into(),
+            response_dst: MsgAddress::NULL,
+            custom_payload: None,
+        };
+        let body: Cell = "x{595F07BC0000000000000001203E81_}".parse().unwrap();
 
-    fn parse(parser: &mut CellParser<'de>, _: Self::Args) -> Result<Self, CellParserError<'de>> {
-        parser.unpack::<ConstU32<JETTON_BURN_TAG>>(())?;
-        Ok(Self {
-            query_id: parser.unpack(())?,
-            amount: parser.unpack_as::<_, VarInt<4>>(())?,
-            response_dst: parser.unpack(())?,
-            custom_payload: parser.parse_as::<_, Option<Ref<ParseFully>>>(NoArgs::EMPTY)?,
-        })
+        assert_eq!(burn.to_cell(()).unwrap(), body);
+        assert_eq!(body.parse_fully::<JettonBurn>(()).unwrap(), burn);
     }
 }
@@ -322,6 +322,7 @@ mod tests {
             BagOfCellsArgs {
                 has_idx: false,
                 has_crc32c: true,
+                ..Default::default()
             },
         )
         .unwrap();
@@ -13,10 +13,11 @@ use tlb::{
     Context, Error, StringError,
     bits::{
         NBits, NoArgs, VarLen,
-        bitvec::{order::Msb0, vec::BitVec},
+        bitvec::{order::Msb0, slice::BitSlice, vec::BitVec},
         de::{BitReader, BitReaderExt, BitUnpack},
         ser::{BitPack, BitWriter, BitWriterExt},
     },
+    hashmap::{DictKey, aug::Key},
     ser::{CellBuilderError, CellSerialize, CellSerializeExt},
 };
 
@@ -336,6 +337,51 @@ impl<'de> BitUnpack<'de> for MsgAddress {
     }
 }
 
+/// Dictionary keys are always encoded as `addr_std$10` without anycast,
+/// i.e. 267 bits, so `workchain_id` has to fit into `int8`
+impl DictKey for MsgAddress {
+    const BITS: u32 = 2 + 1 + 8 + 256;
+
+    fn to_key(&self) -> Result<Key, StringError> {
+        let workchain_id: i8 = self.workchain_id.try_into().map_err(|_| {
+            Error::custom(format!(
+                "workchain_id {} does not fit into addr_std$10",
+                self.workchain_id
+            ))
+        })?;
+        let mut key = Key::with_capacity(Self::BITS as usize);
+        key.pack(MsgAddressTag::Std, ())?
+            // anycast:(Maybe Anycast)
+            .pack::<Option<Anycast>>(None, ())?
+            // workchain_id:int8
+            .pack(workchain_id, ())?
+            // address:bits256
+            .pack(self.address, ())?;
+        Ok(key)
+    }
+
+    fn from_key(mut key: &BitSlice<u8, Msb0>) -> Result<Self, StringError> {
+        let tag: MsgAddressTag = key.unpack(())?;
+        if !matches!(tag, MsgAddressTag::Std) {
+            return Err(Error::custom(format!("expected addr_std$10, got {tag}")));
+        }
+        // anycast:(Maybe Anycast)
+        if key.unpack::<Option<Anycast>>(())?.is_some() {
+            return Err(Error::custom("anycast is not supported"));
+        }
+        let addr = Self {
+            // workchain_id:int8
+            workchain_id: key.unpack::<i8>(())? as i32,
+            // address:bits256
+            address: key.unpack(())?,
+        };
+        if !key.is_empty() {
+            return Err(Error::custom("more data left"));
+        }
+        Ok(addr)
+    }
+}
+
 #[derive(Clone, Copy, Display)]
 #[repr(u8)]
 enum MsgAddressTag {
@@ -444,6 +490,30 @@ mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn dict_key() {
+        let addr: MsgAddress = "EQBGXZ9ddZeWypx8EkJieHJX75ct0bpkmu0Y4YoYr3NM0Z9e"
+            .parse()
+            .unwrap();
+        let key = addr.to_key().unwrap();
+        assert_eq!(key.len(), MsgAddress::BITS as usize);
+        assert_eq!(MsgAddress::from_key(&key).unwrap(), addr);
+
+        // NULL is still encoded as addr_std$10
+        let null = MsgAddress::NULL.to_key().unwrap();
+        assert_eq!(null.len(), MsgAddress::BITS as usize);
+        assert_eq!(MsgAddress::from_key(&null).unwrap(), MsgAddress::NULL);
+
+        assert!(
+            MsgAddress {
+                workchain_id: 1000,
+                ..addr
+            }
+            .to_key()
+            .is_err()
+        );
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn serde() {
@@ -1,9 +1,11 @@
 //! Collection of types to work with currencies
+use core::ops::AddAssign;
+
 use lazy_static::lazy_static;
 use num_bigint::BigUint;
 use num_traits::One;
 use tlb::{
-    Data, Same,
+    Augmentation, Data, Same,
     bits::{VarInt, de::BitReaderExt, ser::BitWriterExt},
     de::{CellDeserialize, CellParser, CellParserError},
     hashmap::HashmapE,
@@ -65,6 +67,32 @@ impl<'de> CellDeserialize<'de> for CurrencyCollection {
     }
 }
 
+impl AddAssign<&Self> for CurrencyCollection {
+    #[inline]
+    fn add_assign(&mut self, rhs: &Self) {
+        self.grams += &rhs.grams;
+        self.other += &rhs.other;
+    }
+}
+
+/// Sum of all currencies in augmented tree. Its
+/// [`fork()`](Augmentation::fork) can also be reused by augmentations of
+/// other values with `CurrencyCollection` extra, e.g. fees of `AccountBlock`
+/// in `AccountBlocks`
+impl Augmentation<Self> for CurrencyCollection {
+    #[inline]
+    fn leaf(value: &Self) -> Self {
+        value.clone()
+    }
+
+    #[inline]
+    fn fork(left: &Self, right: &Self) -> Self {
+        let mut total = left.clone();
+        total += right;
+        total
+    }
+}
+
 /// ```tlb
 /// extra_currencies$_ dict:(HashmapE 32 (VarUInteger 32)) = ExtraCurrencyCollection;
 /// ```
@@ -92,9 +120,22 @@ impl<'de> CellDeserialize<'de> for ExtraCurrencyCollection {
     }
 }
 
+impl AddAssign<&Self> for ExtraCurrencyCollection {
+    fn add_assign(&mut self, rhs: &Self) {
+        for (id, amount) in &rhs.0 {
+            *self.0.entry(id).or_default() += amount;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use tlb::ser::CellSerializeExt;
+    use tlb::{
+        Verified,
+        bits::bitvec::view::AsBits,
+        hashmap::aug::HashmapAugE,
+        ser::{CellSerializeExt, CellSerializeWrapAsExt},
+    };
 
     use super::*;
 
@@ -107,4 +148,36 @@ mod tests {
 
         assert_eq!(got, v);
     }
+
+    #[test]
+    fn currency_collection_augmentation() {
+        let currency = |grams: u32, other: &[(u32, u32)]| CurrencyCollection {
+            grams: grams.into(),
+            other: ExtraCurrencyCollection(
+                other
+                    .iter()
+                    .map(|&(id, amount)| (id.to_be_bytes().as_bits().to_bitvec(), amount.into()))
+                    .collect(),
+            ),
+        };
+        let hm: HashmapAugE<CurrencyCollection, CurrencyCollection> = [
+            currency(1, &[(7, 2)]),
+            currency(10, &[]),
+            currency(100, &[(7, 3), (9, 1)]),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, v)| ((i as u8).to_be_bytes().as_bits().to_bitvec(), v))
+        .collect();
+        assert_eq!(hm.extra, currency(111, &[(7, 5), (9, 1)]));
+
+        let cell = hm
+            .wrap_as::<HashmapAugE<Same, Same>>()
+            .to_cell((8, (), ()))
+            .unwrap();
+        let got: HashmapAugE<CurrencyCollection, CurrencyCollection> = cell
+            .parse_fully_as::<_, Verified<HashmapAugE<Same, Same>>>((8, (), ()))
+            .unwrap();
+        assert_eq!(got.extra, hm.extra);
+    }
 }
@@ -4,8 +4,8 @@ use tlb::{
     Cell, ParseFully, Ref, Same,
     bits::{
         NBits, NoArgs,
-        de::{BitReader, BitReaderExt, BitUnpack},
-        ser::{BitPack, BitWriter, BitWriterExt},
+        de::{BitReaderExt, BitUnpack},
+        ser::{BitPack, BitWriterExt},
     },
     de::{CellDeserialize, CellParser, CellParserError},
     hashmap::HashmapE,
@@ -108,40 +108,13 @@ where
 /// tick_tock$_ tick:Bool tock:Bool = TickTock;
 /// ```
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BitPack, BitUnpack)]
+#[bits(crate = "::tlb::bits")]
 pub struct TickTock {
     pub tick: bool,
     pub tock: bool,
 }
 
-impl BitPack for TickTock {
-    type Args = ();
-
-    #[inline]
-    fn pack<W>(&self, writer: &mut W, _: Self::Args) -> Result<(), W::Error>
-    where
-        W: BitWriter + ?Sized,
-    {
-        writer.pack(self.tick, ())?.pack(self.tock, ())?;
-        Ok(())
-    }
-}
-
-impl<'de> BitUnpack<'de> for TickTock {
-    type Args = ();
-
-    #[inline]
-    fn unpack<R>(reader: &mut R, _: Self::Args) -> Result<Self, R::Error>
-    where
-        R: BitReader<'de> + ?Sized,
-    {
-        Ok(Self {
-            tick: reader.unpack(())?,
-            tock: reader.unpack(())?,
-        })
-    }
-}
-
 /// `library` field for [`StateInit`]
 /// ```tlb
 /// simple_lib$_ public:Bool root:^Cell = SimpleLib;
@@ -0,0 +1,94 @@
+use core::marker::PhantomData;
+
+use crate::{
+    StringError,
+    de::{CellDeserializeAs, CellParser, CellParserError},
+    ser::{CellBuilder, CellBuilderError, CellSerializeAs},
+};
+
+/// Computation of `extra:Y` values of augmented trees, such as
+/// [`HashmapAugE`](super::hashmap::aug::HashmapAugE) and
+/// [`BinTreeAug`](super::bin_tree::aug::BinTreeAug), where extra of each
+/// fork aggregates extras of its children, e.g. total balance of all
+/// accounts in `ShardAccounts`.
+///
+/// Extra of an empty tree is [`Default::default()`].
+/// ```rust
+/// # use tlb::Augmentation;
+/// /// Sum of all values
+/// #[derive(Default, PartialEq)]
+/// struct Total(u64);
+///
+/// impl Augmentation<u32> for Total {
+///     fn leaf(value: &u32) -> Self {
+///         Self(*value as u64)
+///     }
+///
+///     fn fork(left: &Self, right: &Self) -> Self {
+///         Self(left.0 + right.0)
+///     }
+/// }
+/// ```
+pub trait Augmentation<T>: Sized {
+    /// Extra of the leaf with given value
+    fn leaf(value: &T) -> Self;
+
+    /// Extra of the fork with given extras of its children
+    fn fork(left: &Self, right: &Self) -> Self;
+}
+
+/// No extra, as in non-augmented trees
+impl<T> Augmentation<T> for () {
+    #[inline]
+    fn leaf(_: &T) -> Self {}
+
+    #[inline]
+    fn fork(_: &Self, _: &Self) -> Self {}
+}
+
+/// Augmented trees, where extras of nodes can be checked against values
+/// in leaves
+pub trait Augmented {
+    /// Check that extras of all nodes match values in leaves
+    fn verify(&self) -> Result<(), StringError>;
+}
+
+/// Adapter to **de**serialize augmented tree as `As` and
+/// [verify](Augmented::verify) its extras, e.g.
+/// `Verified<HashmapAugE<Data, Data>>`. **Ser**ializes as `As`.
+///
+/// Parsing as `As` alone does not check extras, since it requires to
+/// recompute them for the whole tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Verified<As: ?Sized>(PhantomData<As>);
+
+impl<T, As> CellSerializeAs<T> for Verified<As>
+where
+    As: CellSerializeAs<T> + ?Sized,
+{
+    type Args = As::Args;
+
+    #[inline]
+    fn store_as(
+        source: &T,
+        builder: &mut CellBuilder,
+        args: Self::Args,
+    ) -> Result<(), CellBuilderError> {
+        As::store_as(source, builder, args)
+    }
+}
+
+impl<'de, T, As> CellDeserializeAs<'de, T> for Verified<As>
+where
+    T: Augmented,
+    As: CellDeserializeAs<'de, T> + ?Sized,
+{
+    type Args = As::Args;
+
+    #[inline]
+    fn parse_as(parser: &mut CellParser<'de>, args: Self::Args) -> Result<T, CellParserError<'de>> {
+        let v = parser.parse_as::<_, As>(args)?;
+        v.verify()?;
+        Ok(v)
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
-    r#as::{ParseFully, Ref},
+    Context, Error, StringError,
+    r#as::{Augmentation, Augmented, ParseFully, Ref},
     bits::{de::BitReaderExt, ser::BitWriterExt},
     de::{CellDeserializeAs, CellParser, CellParserError},
     ser::{CellBuilder, CellBuilderError, CellSerializeAs},
@@ -11,11 +12,79 @@ use crate::{
 /// bta_fork$1 {X:Type} {Y:Type} left:^(BinTreeAug X Y)
 /// right:^(BinTreeAug X Y) extra:Y = BinTreeAug X Y;
 /// ```
+#[derive(Debug, Clone)]
 pub struct BinTreeAug<T, E = ()> {
     pub node: BinTreeNode<T, E>,
     pub extra: E,
 }
 
+impl<T, E> BinTreeAug<T, E>
+where
+    E: Augmentation<T>,
+{
+    /// Create leaf with extra computed from its value
+    #[inline]
+    pub fn leaf(value: T) -> Self {
+        Self {
+            extra: E::leaf(&value),
+            node: BinTreeNode::Leaf(value),
+        }
+    }
+
+    /// Create fork with extra computed from extras of its children
+    #[inline]
+    pub fn fork(left: Self, right: Self) -> Self {
+        Self {
+            extra: E::fork(&left.extra, &right.extra),
+            node: BinTreeNode::Fork([Box::new(left), Box::new(right)]),
+        }
+    }
+
+    /// Recompute extras of all nodes, which is needed after values were
+    /// modified in place
+    pub fn recompute(&mut self) {
+        if let BinTreeNode::Fork([left, right]) = &mut self.node {
+            left.recompute();
+            right.recompute();
+        }
+        self.extra = self.node_extra();
+    }
+
+    /// Check that extras of all nodes match values in leaves
+    pub fn verify(&self) -> Result<(), StringError>
+    where
+        E: PartialEq,
+    {
+        if let BinTreeNode::Fork([left, right]) = &self.node {
+            left.verify().context("left")?;
+            right.verify().context("right")?;
+        }
+        if self.extra != self.node_extra() {
+            return Err(Error::custom("extra mismatch"));
+        }
+        Ok(())
+    }
+
+    /// Extra computed from the value in leaf or extras of children
+    #[inline]
+    fn node_extra(&self) -> E {
+        match &self.node {
+            BinTreeNode::Leaf(value) => E::leaf(value),
+            BinTreeNode::Fork([left, right]) => E::fork(&left.extra, &right.extra),
+        }
+    }
+}
+
+impl<T, E> Augmented for BinTreeAug<T, E>
+where
+    E: Augmentation<T> + PartialEq,
+{
+    #[inline]
+    fn verify(&self) -> Result<(), StringError> {
+        BinTreeAug::verify(self)
+    }
+}
+
 impl<T, AsT, E, AsE> CellSerializeAs<BinTreeAug<T, E>> for BinTreeAug<AsT, AsE>
 where
     AsT: CellSerializeAs<T>,
@@ -38,6 +107,7 @@ where
     }
 }
 
+/// Extras are not verified, use [`Verified`](crate::Verified) adapter for that
 impl<'de, T, AsT, E, AsE> CellDeserializeAs<'de, BinTreeAug<T, E>> for BinTreeAug<AsT, AsE>
 where
     AsT: CellDeserializeAs<'de, T>,
@@ -67,6 +137,7 @@ where
 /// bta_fork$1 {X:Type} {Y:Type} left:^(BinTreeAug X Y)
 /// right:^(BinTreeAug X Y) extra:Y = BinTreeAug X Y;
 /// ```
+#[derive(Debug, Clone)]
 pub enum BinTreeNode<T, E = ()> {
     Leaf(T),
     Fork([Box<BinTreeAug<T, E>>; 2]),
@@ -121,3 +192,57 @@ where
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{Cell, Data, Verified};
+
+    use super::*;
+
+    /// Number of leaves
+    impl Augmentation<u8> for u16 {
+        fn leaf(_: &u8) -> Self {
+            1
+        }
+
+        fn fork(left: &Self, right: &Self) -> Self {
+            left + right
+        }
+    }
+
+    fn store(tree: &BinTreeAug<u8, u16>) -> Cell {
+        let mut builder = Cell::builder();
+        builder
+            .store_as::<_, &BinTreeAug<Data, Data>>(tree, ((), ()))
+            .unwrap();
+        builder.into_cell().unwrap()
+    }
+
+    fn parse(cell: &Cell) -> Result<BinTreeAug<u8, u16>, StringError> {
+        cell.parse_fully_as::<_, Verified<BinTreeAug<Data, Data>>>(((), ()))
+    }
+
+    #[test]
+    fn augmented() {
+        let mut tree = BinTreeAug::fork(
+            BinTreeAug::leaf(1),
+            BinTreeAug::fork(BinTreeAug::leaf(2), BinTreeAug::leaf(3)),
+        );
+        assert_eq!(tree.extra, 3);
+        tree.verify().unwrap();
+        assert_eq!(parse(&store(&tree)).unwrap().extra, 3);
+
+        if let BinTreeNode::Fork([_, right]) = &mut tree.node {
+            right.extra = 1;
+        }
+        assert!(tree.verify().is_err());
+        assert!(parse(&store(&tree)).is_err());
+        // extras are verified only on demand
+        store(&tree)
+            .parse_fully_as::<BinTreeAug<u8, u16>, BinTreeAug<Data, Data>>(((), ()))
+            .unwrap();
+
+        tree.recompute();
+        tree.verify().unwrap();
+    }
+}
@@ -1,14 +1,33 @@
 use core::marker::PhantomData;
 
-use crate::de::{CellDeserializeAs, CellParser, CellParserError};
+use crate::{
+    de::{CellDeserializeAs, CellParser, CellParserError},
+    ser::{CellBuilder, CellBuilderError, CellSerializeAs},
+};
 
 use super::Same;
 
 /// Adapter to **de**serialize value and ensure that no more data and references
-/// left.
+/// left. **Ser**ializes as `As`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ParseFully<As: ?Sized = Same>(PhantomData<As>);
 
+impl<T, As> CellSerializeAs<T> for ParseFully<As>
+where
+    As: CellSerializeAs<T> + ?Sized,
+{
+    type Args = As::Args;
+
+    #[inline]
+    fn store_as(
+        source: &T,
+        builder: &mut CellBuilder,
+        args: Self::Args,
+    ) -> Result<(), CellBuilderError> {
+        As::store_as(source, builder, args)
+    }
+}
+
 impl<'de, T, As> CellDeserializeAs<'de, T> for ParseFully<As>
 where
     As: CellDeserializeAs<'de, T> + ?Sized,
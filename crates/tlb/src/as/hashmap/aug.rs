@@ -1,8 +1,12 @@
-use std::iter::once;
+use std::{
+    collections::{BTreeMap, HashMap},
+    iter::once,
+    mem,
+};
 
 use crate::{
-    Context, Error,
-    r#as::{ParseFully, Ref, Same},
+    Context, Error, StringError,
+    r#as::{Augmentation, Augmented, ParseFully, Ref, Same},
     bits::{
         bitvec::{order::Msb0, slice::BitSlice, vec::BitVec},
         de::BitReaderExt,
@@ -13,7 +17,10 @@ use crate::{
 };
 use impl_tools::autoimpl;
 
-use super::hm_label::HmLabel;
+use super::{
+    entry::{Entry, OccupiedEntry, VacantEntry},
+    hm_label::HmLabel,
+};
 
 /// [`HashmapAugE n X Y`](https://docs.ton.org/develop/data-formats/tl-b-types#hashmapauge).  
 /// When `E = ()` it is equivalent to [`HashmapE n X`](https://docs.ton.org/develop/data-formats/tl-b-types#hashmap)
@@ -31,6 +38,124 @@ pub struct HashmapAugE<T, E = ()> {
     pub extra: E,
 }
 
+impl<T, E> HashmapAugE<T, E>
+where
+    E: Augmentation<T> + Default,
+{
+    /// Insert value at given key and return the previous one, if any.
+    ///
+    /// Extras are recomputed along the path to the key, as well as the
+    /// extra of the whole hashmap.
+    ///
+    /// # Panics
+    ///
+    /// Same as [`HashmapE::insert()`], see [`.try_insert()`](HashmapAugE::try_insert)
+    #[inline]
+    pub fn insert(&mut self, key: impl AsRef<BitSlice<u8, Msb0>>, value: T) -> Option<T> {
+        self.try_insert(key, value)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Same as [`.insert()`](HashmapAugE::insert), but returns an error
+    /// if length of the key differs from lengths of keys already present
+    #[inline]
+    pub fn try_insert(
+        &mut self,
+        key: impl AsRef<BitSlice<u8, Msb0>>,
+        value: T,
+    ) -> Result<Option<T>, StringError> {
+        let old = self.m.insert_augmented(key.as_ref(), value)?;
+        self.update_extra();
+        Ok(old)
+    }
+
+    /// Remove value at given key and return it, if any.
+    ///
+    /// Extras are recomputed along the path to the key, as well as the
+    /// extra of the whole hashmap.
+    #[inline]
+    pub fn remove(&mut self, key: impl AsRef<BitSlice<u8, Msb0>>) -> Option<T> {
+        let value = self.m.remove_augmented(key.as_ref());
+        self.update_extra();
+        value
+    }
+
+    /// Retain only values for which `f` returns `true` and recompute
+    /// extras
+    #[inline]
+    pub fn retain(&mut self, f: impl FnMut(&BitSlice<u8, Msb0>, &mut T) -> bool) {
+        self.m.retain_augmented(f);
+        self.update_extra();
+    }
+
+    /// Recompute extras of all nodes, which is needed after values were
+    /// modified in place, e.g. with [`.get_mut()`](HashmapE::get_mut)
+    #[inline]
+    pub fn recompute(&mut self) {
+        if let HashmapE::Root(root) = &mut self.m {
+            root.recompute();
+        }
+        self.update_extra();
+    }
+
+    /// Check that extras of all nodes, as well as the extra of the whole
+    /// hashmap, match values in leaves
+    pub fn verify(&self) -> Result<(), StringError>
+    where
+        E: PartialEq,
+    {
+        let extra = match &self.m {
+            HashmapE::Empty => E::default(),
+            HashmapE::Root(root) => {
+                root.verify_in(&mut Key::new())?;
+                root.root_extra()
+            }
+        };
+        if self.extra != extra {
+            return Err(Error::custom("extra mismatch"));
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn update_extra(&mut self) {
+        self.extra = match &self.m {
+            HashmapE::Empty => E::default(),
+            HashmapE::Root(root) => root.root_extra(),
+        };
+    }
+}
+
+impl<T, E> Augmented for HashmapAugE<T, E>
+where
+    E: Augmentation<T> + PartialEq + Default,
+{
+    #[inline]
+    fn verify(&self) -> Result<(), StringError> {
+        HashmapAugE::verify(self)
+    }
+}
+
+/// Build hashmap from key-value pairs and compute extras, see
+/// [`HashmapE::from_iter()`]
+impl<T, E> FromIterator<(Key, T)> for HashmapAugE<T, E>
+where
+    E: Augmentation<T> + Default,
+{
+    #[inline]
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (Key, T)>,
+    {
+        let mut hm = Self {
+            m: iter.into_iter().collect(),
+            extra: E::default(),
+        };
+        hm.update_extra();
+        hm
+    }
+}
+
 impl<T, AsT, E, AsE> CellSerializeAs<HashmapAugE<T, E>> for HashmapAugE<AsT, AsE>
 where
     AsT: CellSerializeAs<T>,
@@ -56,6 +181,7 @@ where
     }
 }
 
+/// Extras are not verified, use [`Verified`](crate::Verified) adapter for that
 impl<'de, T, AsT, E, AsE> CellDeserializeAs<'de, HashmapAugE<T, E>> for HashmapAugE<AsT, AsE>
 where
     AsT: CellDeserializeAs<'de, T>,
@@ -149,6 +275,127 @@ impl<T, E> HashmapE<T, E> {
     }
 }
 
+impl<T, E> HashmapE<T, E>
+where
+    E: Augmentation<T>,
+{
+    /// Same as [`.insert()`](HashmapE::insert), but recomputes extras
+    /// along the path to the key
+    fn insert_augmented(
+        &mut self,
+        key: &BitSlice<u8, Msb0>,
+        value: T,
+    ) -> Result<Option<T>, StringError> {
+        let root = match mem::take(self) {
+            Self::Empty => {
+                *self = Self::Root(Hashmap::leaf(key.to_bitvec(), value));
+                return Ok(None);
+            }
+            Self::Root(root) => root,
+        };
+        let n = root.key_len();
+        if key.len() != n {
+            *self = Self::Root(root);
+            return Err(Error::custom(format!(
+                "key {key}: expected {n} bits, got {}",
+                key.len()
+            )));
+        }
+        let (root, old) = root.insert_entry(key, value);
+        *self = Self::Root(root);
+        Ok(old)
+    }
+
+    /// Same as [`.remove()`](HashmapE::remove), but recomputes extras
+    /// along the path to the key
+    fn remove_augmented(&mut self, key: &BitSlice<u8, Msb0>) -> Option<T> {
+        let Self::Root(root) = mem::take(self) else {
+            return None;
+        };
+        let (root, value) = root.remove_entry(key);
+        if let Some(root) = root {
+            *self = Self::Root(root);
+        }
+        value
+    }
+
+    /// Same as [`.retain()`](HashmapE::retain), but recomputes extras
+    fn retain_augmented(&mut self, mut f: impl FnMut(&BitSlice<u8, Msb0>, &mut T) -> bool) {
+        let Self::Root(root) = mem::take(self) else {
+            return;
+        };
+        if let Some(root) = root.retain_in(&mut Key::new(), &mut f) {
+            *self = Self::Root(root);
+        }
+    }
+}
+
+impl<T> HashmapE<T> {
+    /// Insert value at given key and return the previous one, if any.
+    ///
+    /// Labels are split as needed, so that the tree remains canonical.
+    ///
+    /// # Panics
+    ///
+    /// If length of the key differs from lengths of keys already present,
+    /// see [`.try_insert()`](HashmapE::try_insert)
+    #[inline]
+    pub fn insert(&mut self, key: impl AsRef<BitSlice<u8, Msb0>>, value: T) -> Option<T> {
+        self.try_insert(key, value)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Same as [`.insert()`](HashmapE::insert), but returns an error if
+    /// length of the key differs from lengths of keys already present
+    #[inline]
+    pub fn try_insert(
+        &mut self,
+        key: impl AsRef<BitSlice<u8, Msb0>>,
+        value: T,
+    ) -> Result<Option<T>, StringError> {
+        self.insert_augmented(key.as_ref(), value)
+    }
+
+    /// Remove value at given key and return it, if any.
+    ///
+    /// Labels are merged as needed, so that the tree remains canonical.
+    #[inline]
+    pub fn remove(&mut self, key: impl AsRef<BitSlice<u8, Msb0>>) -> Option<T> {
+        self.remove_augmented(key.as_ref())
+    }
+
+    /// Retain only values for which `f` returns `true`.
+    ///
+    /// Labels are merged as needed, so that the tree remains canonical.
+    #[inline]
+    pub fn retain(&mut self, f: impl FnMut(&BitSlice<u8, Msb0>, &mut T) -> bool) {
+        self.retain_augmented(f);
+    }
+
+    /// Return [`Entry`] for in-place manipulation of the value at given
+    /// key
+    /// ```rust
+    /// # use tlb::{
+    /// #     bits::bitvec::{order::Msb0, view::AsBits},
+    /// #     hashmap::HashmapE,
+    /// # };
+    /// let mut hm: HashmapE<u32> = HashmapE::new();
+    /// for k in [1u8, 2, 1] {
+    ///     *hm.entry(k.to_be_bytes().as_bits::<Msb0>().to_bitvec()).or_default() += 1;
+    /// }
+    /// assert_eq!(hm.get(1u8.to_be_bytes().as_bits()), Some(&2));
+    /// assert_eq!(hm.get(2u8.to_be_bytes().as_bits()), Some(&1));
+    /// ```
+    #[inline]
+    pub fn entry(&mut self, key: Key) -> Entry<'_, T> {
+        if self.contains_key(&key) {
+            Entry::Occupied(OccupiedEntry::new(self, key))
+        } else {
+            Entry::Vacant(VacantEntry::new(self, key))
+        }
+    }
+}
+
 impl<T, AsT, E, AsE> CellSerializeAs<HashmapE<T, E>> for HashmapE<AsT, AsE>
 where
     AsT: CellSerializeAs<T>,
@@ -262,6 +509,122 @@ where
     }
 }
 
+/// Build hashmap from key-value pairs, where the last value wins for
+/// duplicate keys. Labels of the resulting tree are compressed when
+/// it is serialized.
+///
+/// Keys are expected to be of the same length `n`, which is checked on
+/// serialization.
+///
+/// # Panics
+///
+/// If keys are of different lengths, as such keys cannot be represented
+/// in a single tree
+/// ```rust
+/// # use tlb::{
+/// #     bits::bitvec::{order::Msb0, view::AsBits},
+/// #     hashmap::HashmapE,
+/// #     Cell, Data, Same,
+/// # };
+/// let hm: HashmapE<u16> = [(1u8, 777u16), (17, 111), (128, 777)]
+///     .into_iter()
+///     .map(|(k, v)| (k.to_be_bytes().as_bits::<Msb0>().to_bitvec(), v))
+///     .collect();
+///
+/// assert_eq!(hm.len(), 3);
+/// assert_eq!(hm.get(17u8.to_be_bytes().as_bits()), Some(&111));
+/// # let mut builder = Cell::builder();
+/// # builder.store_as::<_, &HashmapE<Data, Same>>(&hm, (8, (), ())).unwrap();
+/// ```
+impl<T, E> FromIterator<(Key, T)> for HashmapE<T, E>
+where
+    E: Augmentation<T>,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (Key, T)>,
+    {
+        let entries: Vec<_> = iter
+            .into_iter()
+            .collect::<BTreeMap<_, _>>()
+            .into_iter()
+            .collect();
+        let Some((first, _)) = entries.first() else {
+            return Self::Empty;
+        };
+        if let Some((key, _)) = entries.iter().find(|(key, _)| key.len() != first.len()) {
+            panic!(
+                "keys of different lengths: {first} has {} bits, {key} has {} bits",
+                first.len(),
+                key.len(),
+            );
+        }
+        Self::Root(Hashmap::from_sorted(entries, 0))
+    }
+}
+
+impl<T, As> CellSerializeAs<BTreeMap<Key, T>> for HashmapE<As>
+where
+    As: CellSerializeAs<T>,
+    As::Args: Clone,
+{
+    /// (n, As::Args)
+    type Args = (u32, As::Args);
+
+    #[inline]
+    fn store_as(
+        source: &BTreeMap<Key, T>,
+        builder: &mut CellBuilder,
+        args: Self::Args,
+    ) -> Result<(), CellBuilderError> {
+        store_map::<_, As>(source, builder, args)
+    }
+}
+
+impl<T, As, S> CellSerializeAs<HashMap<Key, T, S>> for HashmapE<As>
+where
+    As: CellSerializeAs<T>,
+    As::Args: Clone,
+{
+    /// (n, As::Args)
+    type Args = (u32, As::Args);
+
+    #[inline]
+    fn store_as(
+        source: &HashMap<Key, T, S>,
+        builder: &mut CellBuilder,
+        args: Self::Args,
+    ) -> Result<(), CellBuilderError> {
+        store_map::<_, As>(source, builder, args)
+    }
+}
+
+fn store_map<'a, T, As>(
+    source: impl IntoIterator<Item = (&'a Key, &'a T)>,
+    builder: &mut CellBuilder,
+    (n, args): (u32, As::Args),
+) -> Result<(), CellBuilderError>
+where
+    T: 'a,
+    As: CellSerializeAs<T>,
+    As::Args: Clone,
+{
+    let hm: HashmapE<&T> = source
+        .into_iter()
+        .map(|(key, value)| {
+            if key.len() != n as usize {
+                return Err(Error::custom(format!(
+                    "key {key}: expected {n} bits, got {}",
+                    key.len()
+                )));
+            }
+            Ok((key.clone(), value))
+        })
+        .collect::<Result<_, CellBuilderError>>()?;
+    builder.store_as::<_, HashmapE<&As, Same>>(hm, (n, args, ()))?;
+    Ok(())
+}
+
 /// [`Hashmap n X`](https://docs.ton.org/develop/data-formats/tl-b-types#hashmap)  
 /// Type parameter `E` is optional and stands for `extra`, so it can be reused
 /// for [`HashmapAug n X E`](HashmapAugE)
@@ -313,6 +676,249 @@ impl<T, E> Hashmap<T, E> {
     }
 }
 
+impl<T, E> Hashmap<T, E>
+where
+    E: Augmentation<T>,
+{
+    #[inline]
+    fn leaf(prefix: Key, value: T) -> Self {
+        let extra = E::leaf(&value);
+        Self::new(prefix, HashmapAugNode::new(HashmapNode::Leaf(value), extra))
+    }
+
+    #[inline]
+    fn fork(prefix: Key, children: [Box<Self>; 2]) -> Self {
+        let extra = E::fork(&children[0].node.extra, &children[1].node.extra);
+        Self::new(
+            prefix,
+            HashmapAugNode::new(HashmapNode::Fork(children), extra),
+        )
+    }
+
+    /// Prepend `prefix` and the bit `is_right` to the label of given
+    /// child, which is left alone after its sibling was removed
+    #[inline]
+    fn merge(mut prefix: Key, is_right: bool, child: Self) -> Self {
+        prefix.push(is_right);
+        prefix.extend_from_bitslice(&child.prefix);
+        Self {
+            prefix,
+            node: child.node,
+        }
+    }
+
+    /// Length of keys in this tree
+    fn key_len(&self) -> usize {
+        let mut len = 0;
+        let mut hm = self;
+        loop {
+            len += hm.prefix.len();
+            match &hm.node.node {
+                HashmapNode::Leaf(_) => return len,
+                HashmapNode::Fork([left, _]) => {
+                    len += 1;
+                    hm = left;
+                }
+            }
+        }
+    }
+
+    /// Build tree from entries sorted by their distinct keys of the same
+    /// length, which share first `depth` bits
+    fn from_sorted(mut entries: Vec<(Key, T)>, depth: usize) -> Self {
+        if entries.len() == 1 {
+            let (key, value) = entries.pop().unwrap_or_else(|| unreachable!());
+            return Self::leaf(key[depth..].to_bitvec(), value);
+        }
+        let (first, last) = (
+            &entries[0].0[depth..],
+            &entries[entries.len() - 1].0[depth..],
+        );
+        // keys are sorted, so common prefix of first and last keys is
+        // shared by all of them
+        let common = common_prefix_len(first, last);
+        let prefix = first[..common].to_bitvec();
+        let fork = depth + common;
+        let right = entries.split_off(entries.partition_point(|(key, _)| !key[fork]));
+        Self::fork(
+            prefix,
+            [
+                Box::new(Self::from_sorted(entries, fork + 1)),
+                Box::new(Self::from_sorted(right, fork + 1)),
+            ],
+        )
+    }
+
+    /// Insert value at given key of [`.key_len()`](Hashmap::key_len)
+    /// bits, return the resulting tree and the previous value
+    fn insert_entry(self, key: &BitSlice<u8, Msb0>, value: T) -> (Self, Option<T>) {
+        let Self {
+            mut prefix,
+            node: HashmapAugNode { node, extra },
+        } = self;
+        let common = common_prefix_len(&prefix, key);
+        if common < prefix.len() {
+            // labels diverge: split this edge into a fork
+            let mut label = prefix.split_off(common);
+            let is_right = label.remove(0);
+            let old = Box::new(Self::new(label, HashmapAugNode::new(node, extra)));
+            let new = Box::new(Self::leaf(key[common + 1..].to_bitvec(), value));
+            let children = if is_right { [new, old] } else { [old, new] };
+            return (Self::fork(prefix, children), None);
+        }
+        match (node, key[common..].split_first()) {
+            (HashmapNode::Leaf(old), None) => (Self::leaf(prefix, value), Some(old)),
+            (HashmapNode::Fork([left, right]), Some((is_right, key))) => {
+                let (mut child, sibling) = if *is_right {
+                    (right, left)
+                } else {
+                    (left, right)
+                };
+                let (new, old) = (*child).insert_entry(key, value);
+                // reuse the allocation
+                *child = new;
+                let children = if *is_right {
+                    [sibling, child]
+                } else {
+                    [child, sibling]
+                };
+                (Self::fork(prefix, children), old)
+            }
+            _ => unreachable!("key length is checked by the caller"),
+        }
+    }
+
+    /// Remove value at given key, return the remaining tree, if any, and
+    /// the removed value
+    fn remove_entry(self, key: &BitSlice<u8, Msb0>) -> (Option<Self>, Option<T>) {
+        let Some(key) = key.strip_prefix(&self.prefix) else {
+            return (Some(self), None);
+        };
+        let Self {
+            prefix,
+            node: HashmapAugNode { node, extra },
+        } = self;
+        match (node, key.split_first()) {
+            (HashmapNode::Leaf(value), None) => (None, Some(value)),
+            (HashmapNode::Fork([left, right]), Some((is_right, key))) => {
+                let (mut child, sibling) = if *is_right {
+                    (right, left)
+                } else {
+                    (left, right)
+                };
+                let (rest, value) = (*child).remove_entry(key);
+                let Some(rest) = rest else {
+                    // the only child left is merged into this edge
+                    return (Some(Self::merge(prefix, !is_right, *sibling)), value);
+                };
+                // reuse the allocation
+                *child = rest;
+                let children = if *is_right {
+                    [sibling, child]
+                } else {
+                    [child, sibling]
+                };
+                (Some(Self::fork(prefix, children)), value)
+            }
+            (node, _) => (
+                Some(Self::new(prefix, HashmapAugNode::new(node, extra))),
+                None,
+            ),
+        }
+    }
+
+    /// Retain only values for which `f` returns `true`, where `key`
+    /// holds bits of the key preceding this tree
+    fn retain_in(
+        self,
+        key: &mut Key,
+        f: &mut impl FnMut(&BitSlice<u8, Msb0>, &mut T) -> bool,
+    ) -> Option<Self> {
+        let Self {
+            prefix,
+            node: HashmapAugNode { node, .. },
+        } = self;
+        let len = key.len();
+        key.extend_from_bitslice(&prefix);
+        let retained = match node {
+            HashmapNode::Leaf(mut value) => f(key, &mut value).then(|| Self::leaf(prefix, value)),
+            HashmapNode::Fork([mut left, mut right]) => {
+                key.push(false);
+                let l = (*left).retain_in(key, f);
+                key.pop();
+                key.push(true);
+                let r = (*right).retain_in(key, f);
+                key.pop();
+                match (l, r) {
+                    (Some(l), Some(r)) => {
+                        // reuse allocations
+                        (*left, *right) = (l, r);
+                        Some(Self::fork(prefix, [left, right]))
+                    }
+                    (Some(left), None) => Some(Self::merge(prefix, false, left)),
+                    (None, Some(right)) => Some(Self::merge(prefix, true, right)),
+                    (None, None) => None,
+                }
+            }
+        };
+        key.truncate(len);
+        retained
+    }
+
+    /// Recompute extras of all nodes from values in leaves
+    fn recompute(&mut self) {
+        self.node.extra = match &mut self.node.node {
+            HashmapNode::Leaf(value) => E::leaf(value),
+            HashmapNode::Fork([left, right]) => {
+                left.recompute();
+                right.recompute();
+                E::fork(&left.node.extra, &right.node.extra)
+            }
+        };
+    }
+
+    /// Check that extras of all nodes match their values in leaves, where
+    /// `key` holds bits of the key preceding this tree
+    fn verify_in(&self, key: &mut Key) -> Result<(), StringError>
+    where
+        E: PartialEq,
+    {
+        let len = key.len();
+        key.extend_from_bitslice(&self.prefix);
+        let extra = match &self.node.node {
+            HashmapNode::Leaf(value) => E::leaf(value),
+            HashmapNode::Fork([left, right]) => {
+                key.push(false);
+                left.verify_in(key)?;
+                key.pop();
+                key.push(true);
+                right.verify_in(key)?;
+                key.pop();
+                E::fork(&left.node.extra, &right.node.extra)
+            }
+        };
+        if self.node.extra != extra {
+            return Err(Error::custom(format!("extra mismatch at {key}")));
+        }
+        key.truncate(len);
+        Ok(())
+    }
+
+    /// Extra of the whole tree
+    #[inline]
+    fn root_extra(&self) -> E {
+        match &self.node.node {
+            HashmapNode::Leaf(value) => E::leaf(value),
+            HashmapNode::Fork([left, right]) => E::fork(&left.node.extra, &right.node.extra),
+        }
+    }
+}
+
+#[inline]
+fn common_prefix_len(a: &BitSlice<u8, Msb0>, b: &BitSlice<u8, Msb0>) -> usize {
+    a.iter().zip(b.iter()).take_while(|(a, b)| a == b).count()
+}
+
 impl<T, AsT, E, AsE> CellSerializeAs<Hashmap<T, E>> for Hashmap<AsT, AsE>
 where
     AsT: CellSerializeAs<T>,
@@ -672,13 +1278,13 @@ where
 #[cfg(test)]
 mod tests {
     use crate::{
-        Cell, Data,
+        Cell, Data, Verified,
         bits::bitvec::{bits, order::Msb0, view::AsBits},
         ser::{CellSerializeExt, CellSerializeWrapAsExt},
     };
     use std::collections::{BTreeMap, HashMap};
 
-    use super::*;
+    use super::{super::Entry, *};
 
     #[test]
     fn parse() {
@@ -700,7 +1306,7 @@ mod tests {
         builder
             .store_as::<_, HashmapE<Data, Same>>(hm, (8, (), ()))
             .unwrap();
-        let got = builder.into_cell();
+        let got = builder.into_cell().unwrap();
         assert_eq!(got, cell);
     }
 
@@ -734,6 +1340,277 @@ mod tests {
         assert_eq!(hm.get(128u8.to_be_bytes().as_bits()), Some(&777));
     }
 
+    fn key(k: u8) -> Key {
+        k.to_be_bytes().as_bits().to_bitvec()
+    }
+
+    #[test]
+    fn from_iter() {
+        let hm: HashmapE<u16> = [
+            (key(128), 0),
+            (key(17), 111),
+            (key(1), 777),
+            (key(128), 777),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(hm.len(), 3);
+        assert_eq!(
+            hm.wrap_as::<HashmapE<Data, Same>>()
+                .to_cell((8, (), ()))
+                .unwrap(),
+            given_cell_from_example()
+        );
+        assert!(HashmapE::<u16>::from_iter([]).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "keys of different lengths")]
+    fn from_iter_prefix() {
+        let _: HashmapE<()> = [(bits![u8, Msb0; 0].to_bitvec(), ()), (key(1), ())]
+            .into_iter()
+            .collect();
+    }
+
+    #[test]
+    fn std_maps() {
+        let expected = given_cell_from_example();
+        let entries = [(key(1), 777u16), (key(17), 111), (key(128), 777)];
+
+        let btree: BTreeMap<Key, u16> = entries.clone().into_iter().collect();
+        assert_eq!(
+            btree.wrap_as::<HashmapE<Data>>().to_cell((8, ())).unwrap(),
+            expected
+        );
+        let hash: HashMap<Key, u16> = entries.into_iter().collect();
+        assert_eq!(
+            hash.wrap_as::<HashmapE<Data>>().to_cell((8, ())).unwrap(),
+            expected
+        );
+
+        assert!(
+            BTreeMap::from([(key(1), 1u16)])
+                .wrap_as::<HashmapE<Data>>()
+                .to_cell((9, ()))
+                .is_err()
+        );
+        assert_eq!(
+            BTreeMap::<Key, u16>::new()
+                .wrap_as::<HashmapE<Data>>()
+                .to_cell((8, ()))
+                .unwrap(),
+            false.wrap_as::<Data>().to_cell(()).unwrap()
+        );
+    }
+
+    #[test]
+    fn std_maps_roundtrip() {
+        let map: BTreeMap<Key, u32> = (0..200u32)
+            .map(|i| i.wrapping_mul(0x9E37_79B9))
+            .map(|k| (k.to_be_bytes().as_bits().to_bitvec(), k))
+            .collect();
+        let cell = map.wrap_as::<HashmapE<Data>>().to_cell((32, ())).unwrap();
+        let got: BTreeMap<Key, u32> = cell.parse_fully_as::<_, HashmapE<Data>>((32, ())).unwrap();
+        assert_eq!(got, map);
+    }
+
+    #[test]
+    fn insert() {
+        let mut hm = HashmapE::new();
+        assert_eq!(hm.insert(key(128), 0u16), None);
+        assert_eq!(hm.insert(key(1), 777), None);
+        assert_eq!(hm.insert(key(17), 111), None);
+        assert_eq!(hm.insert(key(128), 777), Some(0));
+        assert_eq!(
+            hm.wrap_as::<HashmapE<Data, Same>>()
+                .to_cell((8, (), ()))
+                .unwrap(),
+            given_cell_from_example()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "expected 8 bits")]
+    fn insert_key_len() {
+        let mut hm = HashmapE::new();
+        hm.insert(key(1), ());
+        hm.insert(bits![u8, Msb0; 0, 1], ());
+    }
+
+    #[test]
+    fn try_insert_key_len() {
+        let mut hm = HashmapE::new();
+        assert_eq!(hm.try_insert(key(1), 1).unwrap(), None);
+        assert!(hm.try_insert(bits![u8, Msb0; 0, 1], 2).is_err());
+        assert_eq!(hm.try_insert(key(1), 3).unwrap(), Some(1));
+        assert_eq!(hm, [(key(1), 3)].into_iter().collect());
+    }
+
+    #[test]
+    #[should_panic(expected = "keys of different lengths")]
+    fn from_iter_key_len() {
+        let _: HashmapE<()> = [(key(1), ()), (bits![u8, Msb0; 1, 0].to_bitvec(), ())]
+            .into_iter()
+            .collect();
+    }
+
+    #[test]
+    fn remove() {
+        let mut hm: HashmapE<u16> = [(key(1), 777), (key(17), 111), (key(128), 777)]
+            .into_iter()
+            .collect();
+        assert_eq!(hm.remove(key(2)), None);
+        assert_eq!(hm.remove(key(17)), Some(111));
+        assert_eq!(hm, [(key(1), 777), (key(128), 777)].into_iter().collect());
+        assert_eq!(hm.remove(key(128)), Some(777));
+        assert_eq!(hm.remove(key(1)), Some(777));
+        assert!(hm.is_empty());
+        assert_eq!(hm.remove(key(1)), None);
+    }
+
+    /// Tree stays canonical, i.e. equal to the one built from scratch
+    #[test]
+    fn canonical() {
+        let mut hm = HashmapE::new();
+        let mut model = BTreeMap::new();
+        let mut x = 1u32;
+        for i in 0..500u32 {
+            // xorshift
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            let k = key(x as u8);
+            if x & 0x100 == 0 {
+                assert_eq!(hm.insert(&k, i), model.insert(k, i));
+            } else {
+                assert_eq!(hm.remove(&k), model.remove(&k));
+            }
+            assert_eq!(hm.len(), model.len());
+            assert_eq!(hm, model.clone().into_iter().collect());
+        }
+    }
+
+    #[test]
+    fn retain() {
+        let mut hm: HashmapE<u8> = (0..=255u8).map(|k| (key(k), k)).collect();
+        hm.retain(|k, v| {
+            assert_eq!(*k, *key(*v));
+            *v % 3 == 0 && *v != 0
+        });
+        assert_eq!(
+            hm,
+            (1..=255u8)
+                .filter(|v| v % 3 == 0)
+                .map(|k| (key(k), k))
+                .collect()
+        );
+        hm.retain(|_, _| false);
+        assert!(hm.is_empty());
+    }
+
+    #[test]
+    fn entry() {
+        let mut hm: HashmapE<u16> = HashmapE::new();
+        *hm.entry(key(1)).or_insert(1) += 10;
+        hm.entry(key(1)).and_modify(|v| *v *= 2).or_insert(0);
+        hm.entry(key(2)).and_modify(|v| *v *= 2).or_insert(5);
+        assert_eq!(hm.get(key(1)), Some(&22));
+        assert_eq!(hm.get(key(2)), Some(&5));
+
+        let Entry::Occupied(entry) = hm.entry(key(1)) else {
+            panic!("entry is vacant");
+        };
+        assert_eq!(entry.remove(), 22);
+        assert_eq!(hm, [(key(2), 5)].into_iter().collect());
+    }
+
+    /// Sum of values
+    impl Augmentation<u32> for u64 {
+        fn leaf(value: &u32) -> Self {
+            *value as u64
+        }
+
+        fn fork(left: &Self, right: &Self) -> Self {
+            left + right
+        }
+    }
+
+    #[test]
+    fn augmented() {
+        let mut hm: HashmapAugE<u32, u64> = HashmapAugE::default();
+        for k in [3, 200, 17, 1, 128] {
+            assert_eq!(hm.insert(key(k), k as u32), None);
+        }
+        assert_eq!(hm.extra, 349);
+        hm.verify().unwrap();
+
+        assert_eq!(hm.remove(key(17)), Some(17));
+        assert_eq!(hm.extra, 332);
+        hm.verify().unwrap();
+
+        hm.retain(|_, v| *v > 100);
+        assert_eq!(hm.extra, 328);
+        hm.verify().unwrap();
+
+        *hm.get_mut(key(200)).unwrap() = 1;
+        assert!(hm.verify().is_err());
+        hm.recompute();
+        assert_eq!(hm.extra, 129);
+        hm.verify().unwrap();
+
+        let from_iter: HashmapAugE<u32, u64> =
+            [(key(128), 128), (key(200), 1)].into_iter().collect();
+        assert_eq!(from_iter.m, hm.m);
+        assert_eq!(from_iter.extra, hm.extra);
+
+        hm.remove(key(128));
+        hm.remove(key(200));
+        assert!(hm.is_empty());
+        assert_eq!(hm.extra, 0);
+    }
+
+    #[test]
+    fn augmented_parse() {
+        let hm: HashmapAugE<u32, u64> = [1, 17, 128]
+            .into_iter()
+            .map(|k| (key(k), k as u32))
+            .collect();
+        let store = |hm: &HashmapAugE<u32, u64>| {
+            let mut builder = Cell::builder();
+            builder
+                .store_as::<_, &HashmapAugE<Data, Data>>(hm, (8, (), ()))
+                .unwrap();
+            builder.into_cell().unwrap()
+        };
+        let parse = |cell: &Cell| {
+            cell.parse_fully_as::<HashmapAugE<u32, u64>, Verified<HashmapAugE<Data, Data>>>((
+                8,
+                (),
+                (),
+            ))
+        };
+
+        let got = parse(&store(&hm)).unwrap();
+        assert_eq!((got.m, got.extra), (hm.m.clone(), hm.extra));
+
+        let mut invalid = hm.clone();
+        invalid.extra += 1;
+        assert!(parse(&store(&invalid)).is_err());
+        // extras are verified only on demand
+        let got = store(&invalid)
+            .parse_fully_as::<HashmapAugE<u32, u64>, HashmapAugE<Data, Data>>((8, (), ()))
+            .unwrap();
+        assert_eq!(got.extra, invalid.extra);
+
+        let mut invalid = hm;
+        if let HashmapE::Root(root) = &mut invalid.m {
+            if let HashmapNode::Fork([left, _]) = &mut root.node.node {
+                left.node.extra += 1;
+            }
+        }
+        assert!(parse(&store(&invalid)).is_err());
+    }
+
     /// See <https://docs.ton.org/develop/data-formats/tl-b-types#hashmap-parsing-example>
     fn given_cell_from_example() -> Cell {
         (
@@ -0,0 +1,507 @@
+use core::marker::PhantomData;
+
+use impl_tools::autoimpl;
+use num_bigint::BigUint;
+
+use crate::{
+    Error, StringError,
+    r#as::Same,
+    bits::{
+        NBits,
+        bitvec::{order::Msb0, slice::BitSlice},
+        de::{unpack_fully, unpack_fully_as},
+        ser::{pack, pack_as},
+    },
+    de::{CellDeserialize, CellDeserializeAs, CellParser, CellParserError},
+    ser::{CellBuilder, CellBuilderError, CellSerialize, CellSerializeAs},
+};
+
+use super::{HashmapE, aug::Key};
+
+/// Codec for keys of [`Dict`], which are encoded into bit strings of
+/// fixed length.
+pub trait DictKey: Sized {
+    /// Length of encoded keys, `n` in `HashmapE n X`
+    const BITS: u32;
+
+    /// Whether encoded keys are ordered as two's complement signed
+    /// integers, see [`HashmapE::range()`]
+    const SIGNED: bool = false;
+
+    /// Encode key into exactly [`BITS`](DictKey::BITS) bits
+    fn to_key(&self) -> Result<Key, StringError>;
+
+    /// Decode key from exactly [`BITS`](DictKey::BITS) bits
+    fn from_key(key: &BitSlice<u8, Msb0>) -> Result<Self, StringError>;
+}
+
+/// Adapter to encode keys of [`Dict`] with, same as [`DictKey`] but for
+/// types that need extra parameters, e.g. width of [`BigUint`] keys with
+/// [`NBits`].
+pub trait DictKeyAs<K> {
+    /// Length of encoded keys, `n` in `HashmapE n X`
+    const BITS: u32;
+
+    /// Whether encoded keys are ordered as two's complement signed
+    /// integers, see [`HashmapE::range()`]
+    const SIGNED: bool = false;
+
+    /// Encode key into exactly [`BITS`](DictKeyAs::BITS) bits
+    fn to_key(key: &K) -> Result<Key, StringError>;
+
+    /// Decode key from exactly [`BITS`](DictKeyAs::BITS) bits
+    fn from_key(key: &BitSlice<u8, Msb0>) -> Result<K, StringError>;
+}
+
+impl<K> DictKeyAs<K> for Same
+where
+    K: DictKey,
+{
+    const BITS: u32 = K::BITS;
+    const SIGNED: bool = K::SIGNED;
+
+    #[inline]
+    fn to_key(key: &K) -> Result<Key, StringError> {
+        key.to_key()
+    }
+
+    #[inline]
+    fn from_key(key: &BitSlice<u8, Msb0>) -> Result<K, StringError> {
+        K::from_key(key)
+    }
+}
+
+macro_rules! impl_dict_key_for_integers {
+    ($signed:literal => $($t:ty)+) => {$(
+        impl DictKey for $t {
+            const BITS: u32 = <$t>::BITS;
+            const SIGNED: bool = $signed;
+
+            #[inline]
+            fn to_key(&self) -> Result<Key, StringError> {
+                pack(*self, ())
+            }
+
+            #[inline]
+            fn from_key(key: &BitSlice<u8, Msb0>) -> Result<Self, StringError> {
+                unpack_fully(key, ())
+            }
+        }
+    )+};
+}
+impl_dict_key_for_integers! { false => u8 u16 u32 u64 u128 }
+impl_dict_key_for_integers! { true => i8 i16 i32 i64 i128 }
+
+impl<const N: usize> DictKey for [u8; N] {
+    const BITS: u32 = N as u32 * 8;
+
+    #[inline]
+    fn to_key(&self) -> Result<Key, StringError> {
+        pack(self, ())
+    }
+
+    #[inline]
+    fn from_key(key: &BitSlice<u8, Msb0>) -> Result<Self, StringError> {
+        unpack_fully(key, ())
+    }
+}
+
+/// `uintN` keys
+impl<const BITS: usize> DictKeyAs<BigUint> for NBits<BITS> {
+    const BITS: u32 = BITS as u32;
+
+    #[inline]
+    fn to_key(key: &BigUint) -> Result<Key, StringError> {
+        pack_as::<_, &Self>(key, ())
+    }
+
+    #[inline]
+    fn from_key(key: &BitSlice<u8, Msb0>) -> Result<BigUint, StringError> {
+        unpack_fully_as::<_, Self>(key, ())
+    }
+}
+
+/// [`HashmapE n X`](HashmapE) with typed keys, where `n` is known at
+/// compile time from the key codec `KAs`, which is [`DictKey`]
+/// implementation of `K` by default.
+///
+/// All keys in the dictionary are guaranteed to be decodable, so that it
+/// can be iterated over with typed keys in their natural order.
+/// ```rust
+/// # use tlb::{
+/// #     hashmap::Dict,
+/// #     ser::CellSerializeExt,
+/// #     Cell, Data, StringError,
+/// # };
+/// # fn main() -> Result<(), StringError> {
+/// let mut dict: Dict<i32, u16> = Dict::new();
+/// dict.insert(-1, 1)?;
+/// dict.insert(7, 2)?;
+/// dict.insert(-5, 3)?;
+///
+/// assert_eq!(dict.get(&7), Some(&2));
+/// // signed keys are ordered as integers
+/// assert!(dict.keys().eq([-5, -1, 7]));
+///
+/// let mut builder = Cell::builder();
+/// builder.store_as::<_, &Dict<i32, Data>>(&dict, ())?;
+/// let cell = builder.into_cell()?;
+/// assert_eq!(cell.parse_fully_as::<_, Dict<i32, Data>>(())?, dict);
+/// # Ok(())
+/// # }
+/// ```
+#[autoimpl(Debug, Clone, PartialEq, Eq ignore self._key where V: trait)]
+pub struct Dict<K, V, KAs = Same> {
+    map: HashmapE<V>,
+    _key: PhantomData<fn() -> (K, KAs)>,
+}
+
+impl<K, V, KAs> Default for Dict<K, V, KAs> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V, KAs> Dict<K, V, KAs> {
+    /// Create empty dictionary
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            map: HashmapE::new(),
+            _key: PhantomData,
+        }
+    }
+
+    /// Underlying hashmap with encoded keys
+    #[inline]
+    pub fn as_hashmap(&self) -> &HashmapE<V> {
+        &self.map
+    }
+
+    #[inline]
+    pub fn into_hashmap(self) -> HashmapE<V> {
+        self.map
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+}
+
+impl<K, V, KAs> Dict<K, V, KAs>
+where
+    KAs: DictKeyAs<K>,
+{
+    #[inline]
+    fn decode(key: &BitSlice<u8, Msb0>) -> K {
+        KAs::from_key(key).expect("keys in dictionary are valid")
+    }
+
+    /// Encode key and ensure it is of [`KAs::BITS`](DictKeyAs::BITS) length
+    fn encode(key: &K) -> Result<Key, StringError> {
+        let key = KAs::to_key(key)?;
+        if key.len() != KAs::BITS as usize {
+            return Err(Error::custom(format!(
+                "key {key}: expected {} bits, got {}",
+                KAs::BITS,
+                key.len()
+            )));
+        }
+        Ok(key)
+    }
+
+    /// Returns whether this dictionary contains given key.
+    ///
+    /// Keys that cannot be encoded are never contained.
+    #[inline]
+    pub fn contains_key(&self, key: &K) -> bool {
+        Self::encode(key).is_ok_and(|key| self.map.contains_key(key))
+    }
+
+    /// Returns reference to value associated with given key
+    #[inline]
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.map.get(Self::encode(key).ok()?)
+    }
+
+    /// Returns mutable reference to value associated with given key
+    #[inline]
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.map.get_mut(Self::encode(key).ok()?)
+    }
+
+    /// Insert value at given key and return the previous one, if any.
+    /// Returns an error if the key cannot be encoded into exactly
+    /// [`KAs::BITS`](DictKeyAs::BITS) bits.
+    #[inline]
+    pub fn insert(&mut self, key: K, value: V) -> Result<Option<V>, StringError> {
+        Ok(self.map.insert(Self::encode(&key)?, value))
+    }
+
+    /// Remove given key and return its value, if any
+    #[inline]
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.map.remove(Self::encode(key).ok()?)
+    }
+
+    /// Iterate over entries in ascending order of keys
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (K, &V)> + '_ {
+        self.map
+            .range::<Key>(.., KAs::SIGNED)
+            .map(|(k, v)| (Self::decode(&k), v))
+    }
+
+    /// Iterate over keys in ascending order
+    #[inline]
+    pub fn keys(&self) -> impl Iterator<Item = K> + '_ {
+        self.iter().map(|(k, _)| k)
+    }
+
+    /// Iterate over values in ascending order of their keys
+    #[inline]
+    pub fn values(&self) -> impl Iterator<Item = &V> + '_ {
+        self.iter().map(|(_, v)| v)
+    }
+
+    /// Return entry with the smallest key
+    #[inline]
+    pub fn min(&self) -> Option<(K, &V)> {
+        self.map
+            .min(KAs::SIGNED)
+            .map(|(k, v)| (Self::decode(&k), v))
+    }
+
+    /// Return entry with the largest key
+    #[inline]
+    pub fn max(&self) -> Option<(K, &V)> {
+        self.map
+            .max(KAs::SIGNED)
+            .map(|(k, v)| (Self::decode(&k), v))
+    }
+
+    /// Return entry with the smallest key greater than given one, or
+    /// equal to it when `inclusive`.
+    ///
+    /// Returns `None` if given key cannot be encoded.
+    #[inline]
+    pub fn get_next(&self, key: &K, inclusive: bool) -> Option<(K, &V)> {
+        self.map
+            .get_next(Self::encode(key).ok()?, inclusive, KAs::SIGNED)
+            .map(|(k, v)| (Self::decode(&k), v))
+    }
+
+    /// Return entry with the largest key less than given one, or equal to
+    /// it when `inclusive`.
+    ///
+    /// Returns `None` if given key cannot be encoded.
+    #[inline]
+    pub fn get_prev(&self, key: &K, inclusive: bool) -> Option<(K, &V)> {
+        self.map
+            .get_prev(Self::encode(key).ok()?, inclusive, KAs::SIGNED)
+            .map(|(k, v)| (Self::decode(&k), v))
+    }
+}
+
+impl<K, V, KAs> From<Dict<K, V, KAs>> for HashmapE<V> {
+    #[inline]
+    fn from(dict: Dict<K, V, KAs>) -> Self {
+        dict.map
+    }
+}
+
+/// Checks that all keys are of [`KAs::BITS`](DictKeyAs::BITS) length and
+/// can be decoded
+impl<K, V, KAs> TryFrom<HashmapE<V>> for Dict<K, V, KAs>
+where
+    KAs: DictKeyAs<K>,
+{
+    type Error = StringError;
+
+    fn try_from(map: HashmapE<V>) -> Result<Self, Self::Error> {
+        for key in map.keys() {
+            if key.len() != KAs::BITS as usize {
+                return Err(Error::custom(format!(
+                    "key {key}: expected {} bits, got {}",
+                    KAs::BITS,
+                    key.len()
+                )));
+            }
+            KAs::from_key(&key).map_err(|err| Error::custom(format!("key {key}: {err}")))?;
+        }
+        Ok(Self {
+            map,
+            _key: PhantomData,
+        })
+    }
+}
+
+impl<K, V, KAs, AsV> CellSerializeAs<Dict<K, V, KAs>> for Dict<K, AsV, KAs>
+where
+    AsV: CellSerializeAs<V>,
+    AsV::Args: Clone,
+    KAs: DictKeyAs<K>,
+{
+    type Args = AsV::Args;
+
+    #[inline]
+    fn store_as(
+        source: &Dict<K, V, KAs>,
+        builder: &mut CellBuilder,
+        args: Self::Args,
+    ) -> Result<(), CellBuilderError> {
+        builder.store_as::<_, &HashmapE<AsV, Same>>(&source.map, (KAs::BITS, args, ()))?;
+        Ok(())
+    }
+}
+
+impl<K, V, KAs> CellSerialize for Dict<K, V, KAs>
+where
+    V: CellSerialize,
+    V::Args: Clone,
+    KAs: DictKeyAs<K>,
+{
+    type Args = V::Args;
+
+    #[inline]
+    fn store(&self, builder: &mut CellBuilder, args: Self::Args) -> Result<(), CellBuilderError> {
+        builder.store_as::<_, &Dict<K, Same, KAs>>(self, args)?;
+        Ok(())
+    }
+}
+
+impl<'de, K, V, KAs, AsV> CellDeserializeAs<'de, Dict<K, V, KAs>> for Dict<K, AsV, KAs>
+where
+    AsV: CellDeserializeAs<'de, V>,
+    AsV::Args: Clone,
+    KAs: DictKeyAs<K>,
+{
+    type Args = AsV::Args;
+
+    #[inline]
+    fn parse_as(
+        parser: &mut CellParser<'de>,
+        args: Self::Args,
+    ) -> Result<Dict<K, V, KAs>, CellParserError<'de>> {
+        parser
+            .parse_as::<HashmapE<V>, HashmapE<AsV, Same>>((KAs::BITS, args, ()))?
+            .try_into()
+    }
+}
+
+impl<'de, K, V, KAs> CellDeserialize<'de> for Dict<K, V, KAs>
+where
+    V: CellDeserialize<'de>,
+    V::Args: Clone,
+    KAs: DictKeyAs<K>,
+{
+    type Args = V::Args;
+
+    #[inline]
+    fn parse(parser: &mut CellParser<'de>, args: Self::Args) -> Result<Self, CellParserError<'de>> {
+        parser.parse_as::<_, Dict<K, Same, KAs>>(args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Cell, Data, bits::bitvec::bits};
+
+    use super::*;
+
+    #[test]
+    fn integer_keys() {
+        assert_eq!(
+            0x8001u16.to_key().unwrap(),
+            bits![u8, Msb0; 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]
+        );
+        assert_eq!(
+            i8::from_key(bits![u8, Msb0; 1, 1, 1, 1, 1, 1, 1, 1]).unwrap(),
+            -1
+        );
+        assert!(u8::from_key(bits![u8, Msb0; 1, 1, 1]).is_err());
+        assert_eq!(<[u8; 32]>::BITS, 256);
+    }
+
+    #[test]
+    fn biguint_keys() {
+        type K = NBits<12>;
+        let key = K::to_key(&BigUint::from(0xABCu32)).unwrap();
+        assert_eq!(key.len(), 12);
+        assert_eq!(K::from_key(&key).unwrap(), BigUint::from(0xABCu32));
+        assert!(K::to_key(&BigUint::from(0x1000u32)).is_err());
+    }
+
+    #[test]
+    fn key_len() {
+        /// Encodes into fewer bits than declared
+        struct Short;
+
+        impl DictKeyAs<u8> for Short {
+            const BITS: u32 = 8;
+
+            fn to_key(key: &u8) -> Result<Key, StringError> {
+                pack_as::<_, NBits<4>>(*key, ())
+            }
+
+            fn from_key(key: &BitSlice<u8, Msb0>) -> Result<u8, StringError> {
+                unpack_fully(key, ())
+            }
+        }
+
+        let mut dict: Dict<u8, u8, Short> = Dict::new();
+        assert!(dict.insert(1, 1).is_err());
+        assert!(dict.is_empty());
+        assert!(!dict.contains_key(&1));
+    }
+
+    #[test]
+    fn signed_order() {
+        let mut dict: Dict<i16, i16> = Dict::new();
+        for k in [300, -2, 0, i16::MIN, 5, -300, i16::MAX] {
+            assert_eq!(dict.insert(k, k).unwrap(), None);
+        }
+        assert!(dict.keys().eq([i16::MIN, -300, -2, 0, 5, 300, i16::MAX]));
+        assert_eq!(dict.min().map(|(k, _)| k), Some(i16::MIN));
+        assert_eq!(dict.max().map(|(k, _)| k), Some(i16::MAX));
+        assert_eq!(dict.get_next(&-2, false).map(|(k, _)| k), Some(0));
+        assert_eq!(dict.get_next(&-2, true).map(|(k, _)| k), Some(-2));
+        assert_eq!(dict.get_prev(&1, false).map(|(k, _)| k), Some(0));
+        assert_eq!(dict.remove(&-300), Some(-300));
+        assert!(!dict.contains_key(&-300));
+    }
+
+    #[test]
+    fn serde() {
+        let dict: Dict<BigUint, u8, NBits<12>> = {
+            let mut dict = Dict::new();
+            dict.insert(1u8.into(), 1).unwrap();
+            dict.insert(0xFFFu32.into(), 2).unwrap();
+            dict
+        };
+        let mut builder = Cell::builder();
+        builder
+            .store_as::<_, &Dict<BigUint, Data, NBits<12>>>(&dict, ())
+            .unwrap();
+        let cell = builder.into_cell().unwrap();
+
+        let hm: HashmapE<u8> = cell
+            .parse_fully_as::<_, HashmapE<Data, Same>>((12, (), ()))
+            .unwrap();
+        assert_eq!(&hm, dict.as_hashmap());
+        let got: Dict<BigUint, u8, NBits<12>> = cell
+            .parse_fully_as::<_, Dict<BigUint, Data, NBits<12>>>(())
+            .unwrap();
+        assert_eq!(got, dict);
+
+        // keys of other length are rejected
+        assert!(Dict::<u16, u8>::try_from(hm).is_err());
+    }
+}
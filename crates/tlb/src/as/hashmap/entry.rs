@@ -0,0 +1,141 @@
+use crate::bits::bitvec::{order::Msb0, slice::BitSlice};
+
+use super::{HashmapE, aug::Key};
+
+/// View into a single entry of [`HashmapE`], which is either vacant or
+/// occupied, created with [`.entry()`](HashmapE::entry)
+pub enum Entry<'a, T> {
+    Occupied(OccupiedEntry<'a, T>),
+    Vacant(VacantEntry<'a, T>),
+}
+
+impl<'a, T> Entry<'a, T> {
+    /// Key of this entry
+    #[inline]
+    pub fn key(&self) -> &BitSlice<u8, Msb0> {
+        match self {
+            Self::Occupied(entry) => entry.key(),
+            Self::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Insert given value if the entry is vacant and return mutable
+    /// reference to the value in the entry
+    #[inline]
+    pub fn or_insert(self, value: T) -> &'a mut T {
+        self.or_insert_with(|| value)
+    }
+
+    /// Insert the result of `f` if the entry is vacant and return mutable
+    /// reference to the value in the entry
+    #[inline]
+    pub fn or_insert_with(self, f: impl FnOnce() -> T) -> &'a mut T {
+        match self {
+            Self::Occupied(entry) => entry.into_mut(),
+            Self::Vacant(entry) => entry.insert(f()),
+        }
+    }
+
+    /// Insert default value if the entry is vacant and return mutable
+    /// reference to the value in the entry
+    #[inline]
+    pub fn or_default(self) -> &'a mut T
+    where
+        T: Default,
+    {
+        self.or_insert_with(T::default)
+    }
+
+    /// Modify the value in place if the entry is occupied
+    #[inline]
+    #[must_use]
+    pub fn and_modify(mut self, f: impl FnOnce(&mut T)) -> Self {
+        if let Self::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+/// Occupied [`Entry`]
+pub struct OccupiedEntry<'a, T> {
+    map: &'a mut HashmapE<T>,
+    key: Key,
+}
+
+impl<'a, T> OccupiedEntry<'a, T> {
+    #[inline]
+    pub(super) fn new(map: &'a mut HashmapE<T>, key: Key) -> Self {
+        Self { map, key }
+    }
+
+    #[inline]
+    pub fn key(&self) -> &BitSlice<u8, Msb0> {
+        &self.key
+    }
+
+    #[inline]
+    pub fn get(&self) -> &T {
+        self.map.get(&self.key).expect("entry is occupied")
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.map.get_mut(&self.key).expect("entry is occupied")
+    }
+
+    /// Convert into mutable reference to the value bound to the lifetime
+    /// of the map
+    #[inline]
+    pub fn into_mut(self) -> &'a mut T {
+        self.map.get_mut(&self.key).expect("entry is occupied")
+    }
+
+    /// Replace the value and return the old one
+    #[inline]
+    pub fn insert(&mut self, value: T) -> T {
+        core::mem::replace(self.get_mut(), value)
+    }
+
+    /// Remove the entry from the map and return its value
+    #[inline]
+    pub fn remove(self) -> T {
+        self.map.remove(&self.key).expect("entry is occupied")
+    }
+}
+
+/// Vacant [`Entry`]
+pub struct VacantEntry<'a, T> {
+    map: &'a mut HashmapE<T>,
+    key: Key,
+}
+
+impl<'a, T> VacantEntry<'a, T> {
+    #[inline]
+    pub(super) fn new(map: &'a mut HashmapE<T>, key: Key) -> Self {
+        Self { map, key }
+    }
+
+    #[inline]
+    pub fn key(&self) -> &BitSlice<u8, Msb0> {
+        &self.key
+    }
+
+    #[inline]
+    pub fn into_key(self) -> Key {
+        self.key
+    }
+
+    /// Insert value into the map and return mutable reference to it.
+    ///
+    /// # Panics
+    ///
+    /// Same as [`HashmapE::insert()`]
+    #[inline]
+    pub fn insert(self, value: T) -> &'a mut T {
+        self.map.insert(&self.key, value);
+        self.map
+            .get_mut(&self.key)
+            .expect("value was just inserted")
+    }
+}
@@ -30,24 +30,21 @@ impl BitPackAs<BitSlice<u8, Msb0>> for HmLabel {
     {
         let n = source.len() as u32;
         // {n <= m}
-        // here we check if strictly less as (Unary ~n) needs n+1 bits
-        if n < m {
-            writer
-                // hml_short$0
-                .pack(false, ())?
-                // len:(Unary ~n)
-                .pack_as::<_, Unary>(source.len(), ())?
-                // s:(n * Bit)
-                .write_bitslice(source)?;
-            return Ok(());
+        if n > m {
+            return Err(Error::custom(format!("label is too long: {n} > {m}")));
         }
-
-        let n_bits = m.ilog2() + 1;
-        let v = if source.all() {
-            true
-        } else if source.not_any() {
-            false
-        } else {
+        // pick the shortest encoding, same way as TVM does
+        let n_bits = n_bits(m);
+        let same = (source.all() || source.not_any()) && n > 0;
+        if same && n_bits + 1 < 2 * n {
+            writer
+                // hml_same$11
+                .pack_as::<_, NBits<2>>(0b11, ())?
+                // v:Bit
+                .pack(source[0], ())?
+                // n:(#<= m)
+                .pack_as::<_, VarNBits>(n, n_bits)?;
+        } else if n_bits < n {
             writer
                 // hml_long$10
                 .pack_as::<_, NBits<2>>(0b10, ())?
@@ -55,19 +52,25 @@ impl BitPackAs<BitSlice<u8, Msb0>> for HmLabel {
                 .pack_as::<_, VarNBits>(n, n_bits)?
                 // s:(n * Bit)
                 .write_bitslice(source)?;
-            return Ok(());
-        };
-        writer
-            // hml_same$11
-            .pack_as::<_, NBits<2>>(0b11, ())?
-            // v:Bit
-            .pack(v, ())?
-            // n:(#<= m)
-            .pack_as::<_, VarNBits>(n, n_bits)?;
+        } else {
+            writer
+                // hml_short$0
+                .pack(false, ())?
+                // len:(Unary ~n)
+                .pack_as::<_, Unary>(source.len(), ())?
+                // s:(n * Bit)
+                .write_bitslice(source)?;
+        }
         Ok(())
     }
 }
 
+/// Number of bits in `#<= m`
+#[inline]
+fn n_bits(m: u32) -> u32 {
+    u32::BITS - m.leading_zeros()
+}
+
 impl<'de> BitUnpackAs<'de, BitVec<u8, Msb0>> for HmLabel {
     /// m
     type Args = u32;
@@ -92,7 +95,10 @@ impl<'de> BitUnpackAs<'de, BitVec<u8, Msb0>> for HmLabel {
                 // hml_long$10
                 false => {
                     // n:(#<= m)
-                    let n: u32 = reader.unpack_as::<_, VarNBits>(m.ilog2() + 1)?;
+                    let n: u32 = reader.unpack_as::<_, VarNBits>(n_bits(m))?;
+                    if n > m {
+                        return Err(Error::custom("n > m"));
+                    }
                     // s:(n * Bit)
                     reader.unpack(n as usize)
                 }
@@ -101,10 +107,51 @@ impl<'de> BitUnpackAs<'de, BitVec<u8, Msb0>> for HmLabel {
                     // v:Bit
                     let v: bool = reader.unpack(())?;
                     // n:(#<= m)
-                    let n: u32 = reader.unpack_as::<_, VarNBits>(m.ilog2() + 1)?;
+                    let n: u32 = reader.unpack_as::<_, VarNBits>(n_bits(m))?;
+                    if n > m {
+                        return Err(Error::custom("n > m"));
+                    }
                     Ok(BitVec::repeat(v, n as usize))
                 }
             },
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::bits::{
+        bitvec::{bits, vec::BitVec},
+        de::unpack_fully_as,
+        ser::pack_as,
+    };
+
+    use super::*;
+
+    #[test]
+    fn shortest() {
+        for (label, m, expected) in [
+            // hml_short$0
+            (bits![u8, Msb0;], 0, bits![u8, Msb0; 0, 0]),
+            (bits![u8, Msb0; 1, 0], 7, bits![u8, Msb0; 0, 1, 1, 0, 1, 0]),
+            // hml_long$10
+            (
+                bits![u8, Msb0; 1, 0, 1, 0],
+                4,
+                bits![u8, Msb0; 1, 0, 1, 0, 0, 1, 0, 1, 0],
+            ),
+            // hml_same$11
+            (
+                bits![u8, Msb0; 0, 0, 0, 0, 0, 0, 0],
+                7,
+                bits![u8, Msb0; 1, 1, 0, 1, 1, 1],
+            ),
+        ] {
+            let packed = pack_as::<_, &HmLabel>(label, m).unwrap();
+            assert_eq!(packed, expected, "{label} ~ {m}");
+            let unpacked: BitVec<u8, Msb0> = unpack_fully_as::<_, HmLabel>(&packed, m).unwrap();
+            assert_eq!(unpacked, label);
+        }
+        assert!(pack_as::<_, &HmLabel>(bits![u8, Msb0; 1], 0).is_err());
+    }
+}
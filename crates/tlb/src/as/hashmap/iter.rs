@@ -0,0 +1,509 @@
+use core::{
+    cmp::Ordering,
+    iter::FusedIterator,
+    ops::{Bound, RangeBounds},
+};
+
+use crate::bits::bitvec::{order::Msb0, slice::BitSlice};
+
+use super::{Hashmap, HashmapE, HashmapNode, aug::Key};
+
+/// Order of keys: unsigned keys are compared bit by bit, while signed
+/// ones are two's complement integers, so that the first bit is
+/// inverted. Reverse order inverts all bits.
+#[derive(Debug, Default, Clone, Copy)]
+struct KeyOrder {
+    signed: bool,
+    reverse: bool,
+}
+
+impl KeyOrder {
+    /// Rank of the bit at given position in the key, so that keys are
+    /// ordered by ranks of their bits
+    #[inline]
+    fn rank(self, bit: bool, pos: usize) -> bool {
+        bit ^ (self.signed && pos == 0) ^ self.reverse
+    }
+
+    /// Compare bits of keys starting at given position
+    fn cmp(self, a: &BitSlice<u8, Msb0>, b: &BitSlice<u8, Msb0>, pos: usize) -> Ordering {
+        a.iter()
+            .zip(b.iter())
+            .enumerate()
+            .find(|(_, (a, b))| a != b)
+            .map_or(Ordering::Equal, |(i, (a, b))| {
+                self.rank(*a, pos + i).cmp(&self.rank(*b, pos + i))
+            })
+    }
+}
+
+impl<T, E> HashmapE<T, E> {
+    #[inline]
+    fn root(&self) -> Option<&Hashmap<T, E>> {
+        match self {
+            Self::Empty => None,
+            Self::Root(root) => Some(root),
+        }
+    }
+
+    /// Iterate over entries in ascending order of unsigned keys
+    /// ```rust
+    /// # use tlb::{
+    /// #     bits::bitvec::{order::Msb0, view::AsBits},
+    /// #     hashmap::HashmapE,
+    /// # };
+    /// let hm: HashmapE<u8> = [3u8, 1, 2]
+    ///     .into_iter()
+    ///     .map(|k| (k.to_be_bytes().as_bits::<Msb0>().to_bitvec(), k))
+    ///     .collect();
+    /// assert!(hm.values().copied().eq([1, 2, 3]));
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T, E> {
+        Iter::new(self.root(), KeyOrder::default())
+    }
+
+    /// Iterate over entries in ascending order of unsigned keys with
+    /// mutable references to values
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, E> {
+        IterMut {
+            stack: match self {
+                Self::Empty => Vec::new(),
+                Self::Root(root) => vec![(Key::new(), root)],
+            },
+        }
+    }
+
+    /// Iterate over keys in ascending unsigned order
+    #[inline]
+    pub fn keys(&self) -> impl Iterator<Item = Key> + '_ {
+        self.iter().map(|(k, _)| k)
+    }
+
+    /// Iterate over values in ascending unsigned order of their keys
+    #[inline]
+    pub fn values(&self) -> impl Iterator<Item = &T> + '_ {
+        self.iter().map(|(_, v)| v)
+    }
+
+    /// Return entry with the smallest key, same as TVM `DICTUMIN` or
+    /// `DICTIMIN` when `signed`
+    #[inline]
+    pub fn min(&self, signed: bool) -> Option<(Key, &T)> {
+        Iter::new(
+            self.root(),
+            KeyOrder {
+                signed,
+                reverse: false,
+            },
+        )
+        .next()
+    }
+
+    /// Return entry with the largest key, same as TVM `DICTUMAX` or
+    /// `DICTIMAX` when `signed`
+    #[inline]
+    pub fn max(&self, signed: bool) -> Option<(Key, &T)> {
+        Iter::new(
+            self.root(),
+            KeyOrder {
+                signed,
+                reverse: true,
+            },
+        )
+        .next()
+    }
+
+    /// Return entry with the smallest key greater than given one, or
+    /// equal to it when `inclusive`, same as TVM `DICTUGETNEXT(EQ)` or
+    /// `DICTIGETNEXT(EQ)` when `signed`.
+    ///
+    /// Given key is expected to be of the same length as keys of the
+    /// hashmap.
+    #[inline]
+    pub fn get_next(
+        &self,
+        key: impl AsRef<BitSlice<u8, Msb0>>,
+        inclusive: bool,
+        signed: bool,
+    ) -> Option<(Key, &T)> {
+        Iter::seek(
+            self.root(),
+            key.as_ref(),
+            inclusive,
+            KeyOrder {
+                signed,
+                reverse: false,
+            },
+        )
+        .next()
+    }
+
+    /// Return entry with the largest key less than given one, or equal to
+    /// it when `inclusive`, same as TVM `DICTUGETPREV(EQ)` or
+    /// `DICTIGETPREV(EQ)` when `signed`.
+    ///
+    /// Given key is expected to be of the same length as keys of the
+    /// hashmap.
+    #[inline]
+    pub fn get_prev(
+        &self,
+        key: impl AsRef<BitSlice<u8, Msb0>>,
+        inclusive: bool,
+        signed: bool,
+    ) -> Option<(Key, &T)> {
+        Iter::seek(
+            self.root(),
+            key.as_ref(),
+            inclusive,
+            KeyOrder {
+                signed,
+                reverse: true,
+            },
+        )
+        .next()
+    }
+
+    /// Iterate over entries with keys within given range in ascending
+    /// order, where keys are compared as signed integers when `signed`.
+    ///
+    /// Bounds are expected to be of the same length as keys of the
+    /// hashmap.
+    /// ```rust
+    /// # use tlb::{
+    /// #     bits::bitvec::{order::Msb0, view::AsBits},
+    /// #     hashmap::{aug::Key, HashmapE},
+    /// # };
+    /// let key = |k: i8| -> Key { k.to_be_bytes().as_bits().to_bitvec() };
+    /// let hm: HashmapE<i8> = (-3..=3).map(|k| (key(k), k)).collect();
+    ///
+    /// assert!(hm.range(key(-2)..key(2), true).map(|(_, v)| *v).eq(-2..2));
+    /// // unsigned order puts negative numbers after positive ones
+    /// assert!(hm.range(key(2).., false).map(|(_, v)| *v).eq([2, 3, -3, -2, -1]));
+    /// ```
+    pub fn range<K>(&self, range: impl RangeBounds<K>, signed: bool) -> Range<'_, T, E>
+    where
+        K: AsRef<BitSlice<u8, Msb0>>,
+    {
+        let order = KeyOrder {
+            signed,
+            reverse: false,
+        };
+        let iter = match range.start_bound() {
+            Bound::Included(start) => Iter::seek(self.root(), start.as_ref(), true, order),
+            Bound::Excluded(start) => Iter::seek(self.root(), start.as_ref(), false, order),
+            Bound::Unbounded => Iter::new(self.root(), order),
+        };
+        Range {
+            iter,
+            end: match range.end_bound() {
+                Bound::Included(end) => Bound::Included(end.as_ref().to_bitvec()),
+                Bound::Excluded(end) => Bound::Excluded(end.as_ref().to_bitvec()),
+                Bound::Unbounded => Bound::Unbounded,
+            },
+        }
+    }
+}
+
+impl<'a, T, E> IntoIterator for &'a HashmapE<T, E> {
+    type Item = (Key, &'a T);
+    type IntoIter = Iter<'a, T, E>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, E> IntoIterator for &'a mut HashmapE<T, E> {
+    type Item = (Key, &'a mut T);
+    type IntoIter = IterMut<'a, T, E>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// Iterator over entries of [`HashmapE`], see [`.iter()`](HashmapE::iter)
+#[derive(Clone)]
+pub struct Iter<'a, T, E = ()> {
+    /// Subtrees left to visit with keys preceding their labels, where
+    /// the next one is on top
+    stack: Vec<(Key, &'a Hashmap<T, E>)>,
+    order: KeyOrder,
+}
+
+impl<'a, T, E> Iter<'a, T, E> {
+    #[inline]
+    fn new(root: Option<&'a Hashmap<T, E>>, order: KeyOrder) -> Self {
+        Self {
+            stack: root.map(|root| (Key::new(), root)).into_iter().collect(),
+            order,
+        }
+    }
+
+    /// Create iterator over entries with keys following given one in
+    /// given order, including the key itself when `inclusive`
+    fn seek(
+        root: Option<&'a Hashmap<T, E>>,
+        bound: &BitSlice<u8, Msb0>,
+        inclusive: bool,
+        order: KeyOrder,
+    ) -> Self {
+        let mut stack = Vec::new();
+        let mut key = Key::new();
+        let mut next = root;
+        while let Some(hm) = next.take() {
+            let depth = key.len();
+            let Some(label) = bound.get(depth..depth + hm.prefix.len()) else {
+                break;
+            };
+            match order.cmp(&hm.prefix, label, depth) {
+                // the whole subtree follows the bound
+                Ordering::Greater => stack.push((key, hm)),
+                // the whole subtree precedes the bound
+                Ordering::Less => {}
+                Ordering::Equal => match &hm.node.node {
+                    HashmapNode::Leaf(_) => {
+                        if inclusive {
+                            stack.push((key, hm));
+                        }
+                    }
+                    HashmapNode::Fork(children) => {
+                        key.extend_from_bitslice(&hm.prefix);
+                        let pos = key.len();
+                        let Some(bit) = bound.get(pos).as_deref().copied() else {
+                            break;
+                        };
+                        if !order.rank(bit, pos) {
+                            // the other child follows the bound
+                            let mut other = key.clone();
+                            other.push(!bit);
+                            stack.push((other, &children[!bit as usize]));
+                        }
+                        key.push(bit);
+                        next = Some(&children[bit as usize]);
+                        continue;
+                    }
+                },
+            }
+            break;
+        }
+        Self { stack, order }
+    }
+}
+
+impl<'a, T, E> Iterator for Iter<'a, T, E> {
+    type Item = (Key, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (mut key, mut hm) = self.stack.pop()?;
+        loop {
+            key.extend_from_bitslice(&hm.prefix);
+            match &hm.node.node {
+                HashmapNode::Leaf(value) => return Some((key, value)),
+                HashmapNode::Fork(children) => {
+                    let pos = key.len();
+                    // bit of the child which goes first
+                    let first = self.order.rank(false, pos);
+                    let mut other = key.clone();
+                    other.push(!first);
+                    self.stack.push((other, &children[!first as usize]));
+                    key.push(first);
+                    hm = &children[first as usize];
+                }
+            }
+        }
+    }
+}
+
+impl<T, E> FusedIterator for Iter<'_, T, E> {}
+
+/// Mutable iterator over entries of [`HashmapE`], see
+/// [`.iter_mut()`](HashmapE::iter_mut)
+pub struct IterMut<'a, T, E = ()> {
+    stack: Vec<(Key, &'a mut Hashmap<T, E>)>,
+}
+
+impl<'a, T, E> Iterator for IterMut<'a, T, E> {
+    type Item = (Key, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (mut key, mut hm) = self.stack.pop()?;
+        loop {
+            key.extend_from_bitslice(&hm.prefix);
+            match &mut hm.node.node {
+                HashmapNode::Leaf(value) => return Some((key, value)),
+                HashmapNode::Fork([left, right]) => {
+                    let mut right_key = key.clone();
+                    right_key.push(true);
+                    self.stack.push((right_key, right));
+                    key.push(false);
+                    hm = left;
+                }
+            }
+        }
+    }
+}
+
+impl<T, E> FusedIterator for IterMut<'_, T, E> {}
+
+/// Iterator over entries of [`HashmapE`] within a range, see
+/// [`.range()`](HashmapE::range)
+#[derive(Clone)]
+pub struct Range<'a, T, E = ()> {
+    iter: Iter<'a, T, E>,
+    end: Bound<Key>,
+}
+
+impl<'a, T, E> Iterator for Range<'a, T, E> {
+    type Item = (Key, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value) = self.iter.next()?;
+        let within = match &self.end {
+            Bound::Included(end) => self.iter.order.cmp(&key, end, 0).is_le(),
+            Bound::Excluded(end) => self.iter.order.cmp(&key, end, 0).is_lt(),
+            Bound::Unbounded => true,
+        };
+        if !within {
+            self.iter.stack.clear();
+            return None;
+        }
+        Some((key, value))
+    }
+}
+
+impl<T, E> FusedIterator for Range<'_, T, E> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::bits::bitvec::view::AsBits;
+
+    use super::*;
+
+    fn key(k: u8) -> Key {
+        k.to_be_bytes().as_bits().to_bitvec()
+    }
+
+    /// Keys of values in the map are values themselves
+    fn hashmap() -> HashmapE<u8> {
+        [0, 1, 17, 64, 127, 128, 129, 200, 255]
+            .into_iter()
+            .map(|k| (key(k), k))
+            .collect()
+    }
+
+    fn value(entry: Option<(Key, &u8)>) -> Option<u8> {
+        entry.map(|(k, v)| {
+            assert_eq!(k, key(*v));
+            *v
+        })
+    }
+
+    #[test]
+    fn iter() {
+        let mut hm = hashmap();
+        assert!(
+            hm.values()
+                .copied()
+                .eq([0, 1, 17, 64, 127, 128, 129, 200, 255])
+        );
+        assert!(hm.iter().all(|(k, v)| k == key(*v)));
+
+        for (_, v) in &mut hm {
+            *v = v.wrapping_add(1);
+        }
+        assert!(hm.keys().eq(hm.values().map(|v| key(v.wrapping_sub(1)))));
+
+        assert_eq!(HashmapE::<u8>::Empty.iter().next(), None);
+    }
+
+    #[test]
+    fn min_max() {
+        let hm = hashmap();
+        assert_eq!(value(hm.min(false)), Some(0));
+        assert_eq!(value(hm.max(false)), Some(255));
+        // -128
+        assert_eq!(value(hm.min(true)), Some(128));
+        assert_eq!(value(hm.max(true)), Some(127));
+
+        let one: HashmapE<u8> = [(key(7), 7)].into_iter().collect();
+        assert_eq!(value(one.min(true)), Some(7));
+        assert_eq!(value(one.max(false)), Some(7));
+        assert_eq!(value(HashmapE::<u8>::Empty.min(false)), None);
+    }
+
+    #[test]
+    fn next_prev() {
+        let hm = hashmap();
+        let values: Vec<u8> = hm.values().copied().collect();
+        for k in 0..=u8::MAX {
+            for inclusive in [false, true] {
+                let (next, prev) = (
+                    values
+                        .iter()
+                        .copied()
+                        .find(|v| *v > k || inclusive && *v == k),
+                    values
+                        .iter()
+                        .copied()
+                        .rfind(|v| *v < k || inclusive && *v == k),
+                );
+                assert_eq!(value(hm.get_next(key(k), inclusive, false)), next);
+                assert_eq!(value(hm.get_prev(key(k), inclusive, false)), prev);
+
+                let s = k as i8;
+                let (next, prev) = (
+                    values
+                        .iter()
+                        .map(|v| *v as i8)
+                        .filter(|v| *v > s || inclusive && *v == s)
+                        .min(),
+                    values
+                        .iter()
+                        .map(|v| *v as i8)
+                        .filter(|v| *v < s || inclusive && *v == s)
+                        .max(),
+                );
+                assert_eq!(
+                    value(hm.get_next(key(k), inclusive, true)),
+                    next.map(|v| v as u8)
+                );
+                assert_eq!(
+                    value(hm.get_prev(key(k), inclusive, true)),
+                    prev.map(|v| v as u8)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn range() {
+        let hm = hashmap();
+        assert!(
+            hm.range(key(1)..key(128), false)
+                .map(|(_, v)| *v)
+                .eq([1, 17, 64, 127])
+        );
+        assert!(
+            hm.range(key(2)..=key(128), false)
+                .map(|(_, v)| *v)
+                .eq([17, 64, 127, 128])
+        );
+        assert!(
+            hm.range::<Key>(.., false)
+                .map(|(_, v)| *v)
+                .eq(hm.values().copied())
+        );
+        // [-56, 17)
+        assert!(
+            hm.range(key(200)..key(17), true)
+                .map(|(_, v)| *v)
+                .eq([200, 255, 0, 1])
+        );
+        assert_eq!(hm.range(key(18)..key(17), false).next(), None);
+    }
+}
@@ -1,5 +1,11 @@
 //! Collection of hashmap-like **de**/**ser**ializable data structures
 pub mod aug;
 pub use aug::{Hashmap, HashmapE, HashmapNode};
+mod dict;
+pub use dict::{Dict, DictKey, DictKeyAs};
+mod entry;
+pub use entry::{Entry, OccupiedEntry, VacantEntry};
 mod hm_label;
+mod iter;
+pub use iter::{Iter, IterMut, Range};
 pub mod pfx;
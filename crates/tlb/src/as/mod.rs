@@ -5,6 +5,7 @@
 //! [serde_with](https://docs.rs/serde_with/latest/serde_with).
 //! Please, read their docs for more usage examples.
 mod args;
+mod augmentation;
 pub mod bin_tree;
 mod data;
 mod default;
@@ -16,7 +17,8 @@ mod reference;
 mod same;
 
 pub use self::{
-    args::*, data::*, default::*, from_into::*, fully::*, list::*, reference::*, same::*,
+    args::*, augmentation::*, data::*, default::*, from_into::*, fully::*, list::*, reference::*,
+    same::*,
 };
 
 use crate::{
@@ -63,9 +63,9 @@ where
     ) -> Result<(), CellBuilderError> {
         let mut b = Cell::builder();
         As::store_as(source, &mut b, args)?;
-        let cell = b.into_cell();
+        let cell = b.into_cell()?;
         builder.store_as::<_, Either<Same, Ref>>(
-            if cell.data.len() <= builder.capacity_left() {
+            if cell.data().len() <= builder.capacity_left() {
                 Either::Left
             } else {
                 Either::Right
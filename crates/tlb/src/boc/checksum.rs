@@ -0,0 +1,87 @@
+use crc::{Crc, Digest};
+
+use crate::{
+    Error, StringError,
+    bits::{
+        bitvec::{field::BitField, order::Msb0, slice::BitSlice},
+        ser::BitWriter,
+    },
+};
+
+pub(crate) static CRC_32_ISCSI: Crc<u32> = Crc::<u32>::new(&crc::CRC_32_ISCSI);
+
+/// [`BitWriter`] computing CRC32C of all data written so far, without
+/// buffering it.
+pub(crate) struct Crc32c {
+    digest: Digest<'static, u32>,
+    /// Bits of not yet completed byte, populated from the right
+    pending: u8,
+    /// Number of bits in `pending`
+    pending_len: usize,
+}
+
+impl Crc32c {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            digest: CRC_32_ISCSI.digest(),
+            pending: 0,
+            pending_len: 0,
+        }
+    }
+
+    /// Returns checksum of written data or an error if it was not
+    /// byte-aligned.
+    #[inline]
+    pub fn finalize(self) -> Result<u32, StringError> {
+        if self.pending_len != 0 {
+            return Err(Error::custom("produced stream is not byte-aligned"));
+        }
+        Ok(self.digest.finalize())
+    }
+}
+
+impl BitWriter for Crc32c {
+    type Error = StringError;
+
+    #[inline]
+    fn capacity_left(&self) -> usize {
+        usize::MAX
+    }
+
+    #[inline]
+    fn write_bit(&mut self, bit: bool) -> Result<(), Self::Error> {
+        self.pending = (self.pending << 1) | bit as u8;
+        self.pending_len += 1;
+        if self.pending_len == 8 {
+            self.digest.update(&[self.pending]);
+            self.pending = 0;
+            self.pending_len = 0;
+        }
+        Ok(())
+    }
+
+    fn write_bitslice(&mut self, mut bits: &BitSlice<u8, Msb0>) -> Result<(), Self::Error> {
+        // complete pending byte first
+        while self.pending_len != 0 {
+            let Some((bit, rest)) = bits.split_first() else {
+                return Ok(());
+            };
+            self.write_bit(*bit)?;
+            bits = rest;
+        }
+        // fast path: whole bytes of byte-aligned slice
+        if let Some((None, body, _)) = bits.domain().region() {
+            self.digest.update(body);
+            bits = &bits[body.len() * 8..];
+        }
+        let mut chunks = bits.chunks_exact(8);
+        for byte in &mut chunks {
+            self.digest.update(&[byte.load_be::<u8>()]);
+        }
+        for bit in chunks.remainder() {
+            self.write_bit(*bit)?;
+        }
+        Ok(())
+    }
+}
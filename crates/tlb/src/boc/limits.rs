@@ -0,0 +1,107 @@
+use core::fmt::Display;
+
+use thiserror::Error as ThisError;
+
+use crate::{Cell, Error, StringError};
+
+/// Resource limits enforced while parsing [`BagOfCells`](super::BagOfCells)
+/// from untrusted input.
+///
+/// Header fields are checked before anything they describe is read, so
+/// that a malicious BoC can not cause huge allocations, and cell depth is
+/// checked before any [`Cell`](crate::Cell) is constructed.
+///
+/// [`Default`] imposes no limits other than ones of the format itself,
+/// and cell depth of [`Cell::MAX_DEPTH`](crate::Cell::MAX_DEPTH) enforced
+/// by TVM.
+/// ```rust
+/// # use tlb::{BagOfCells, BocError, BocLimits, Cell};
+/// let packed = BagOfCells::from_root(Cell::default())
+///     .serialize(Default::default())
+///     .unwrap();
+///
+/// let limits = BocLimits {
+///     max_cells: 0,
+///     ..Default::default()
+/// };
+/// assert!(matches!(
+///     BagOfCells::deserialize_limited(&packed, limits),
+///     Err(BocError::TooManyCells { max: 0, got: 1 }),
+/// ));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BocLimits {
+    /// Maximum number of cells, including absent ones
+    pub max_cells: u32,
+    /// Maximum number of roots
+    pub max_roots: u32,
+    /// Maximum value of `tot_cells_size`, i.e. total size of serialized
+    /// cells in bytes
+    pub max_total_bytes: u64,
+    /// Maximum depth of any cell
+    pub max_depth: u16,
+    /// Maximum length of data of any cell in bits
+    pub max_cell_bits: usize,
+}
+
+impl Default for BocLimits {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_cells: u32::MAX,
+            max_roots: u32::MAX,
+            max_total_bytes: u64::MAX,
+            max_depth: Cell::MAX_DEPTH,
+            max_cell_bits: usize::MAX,
+        }
+    }
+}
+
+/// Error returned by [`BagOfCells::deserialize_limited`](super::BagOfCells::deserialize_limited)
+#[derive(Debug, ThisError)]
+pub enum BocError {
+    /// [`BocLimits::max_cells`] exceeded
+    #[error("too many cells: {got}, max: {max}")]
+    TooManyCells { max: u32, got: u32 },
+    /// [`BocLimits::max_roots`] exceeded
+    #[error("too many roots: {got}, max: {max}")]
+    TooManyRoots { max: u32, got: u32 },
+    /// [`BocLimits::max_total_bytes`] exceeded
+    #[error("tot_cells_size is too big: {got}, max: {max}")]
+    TooManyBytes { max: u64, got: u64 },
+    /// [`BocLimits::max_depth`] exceeded
+    #[error("[{index}]: cell is too deep: {got}, max: {max}")]
+    TooDeep { index: usize, max: u16, got: u16 },
+    /// [`BocLimits::max_cell_bits`] exceeded
+    #[error("[{index}]: cell data is too long: {got} bits, max: {max}")]
+    CellTooLong {
+        index: usize,
+        max: usize,
+        got: usize,
+    },
+    /// Malformed BoC
+    #[error("{0}")]
+    Invalid(StringError),
+}
+
+impl Error for BocError {
+    #[inline]
+    fn custom<T>(msg: T) -> Self
+    where
+        T: Display,
+    {
+        Self::Invalid(StringError::custom(msg))
+    }
+
+    #[inline]
+    fn context<C>(self, context: C) -> Self
+    where
+        C: Display,
+    {
+        match self {
+            Self::Invalid(err) => Self::Invalid(err.context(context)),
+            // limit violations are reported as is
+            err => err,
+        }
+    }
+}
@@ -0,0 +1,679 @@
+use std::{
+    cell::{self, OnceCell},
+    iter,
+    sync::Arc,
+};
+
+use crate::{
+    Cell, Context, Error, LevelMask, StringError,
+    bits::{
+        bitvec::{
+            order::Msb0,
+            slice::BitSlice,
+            view::{AsBits, BitView},
+        },
+        de::BitReaderExt,
+    },
+    de::CellParser,
+};
+
+use super::{BocError, BocHeader, BocLimits, CRC_32_ISCSI};
+
+/// Lazy random-access view over serialized [`BagOfCells`](super::BagOfCells).
+///
+/// It borrows given bytes and decodes cells only when they are accessed,
+/// so walking a single path in a large BoC costs only the cells on that
+/// path. Offsets of cells are taken from the index, if present, or
+/// computed by a quick scan of cell descriptors otherwise.
+///
+/// ```rust
+/// # use tlb::{
+/// #     bits::de::BitReaderExt,
+/// #     ser::{CellSerializeExt, CellSerializeWrapAsExt},
+/// #     BagOfCells, BagOfCellsArgs, BocView, Data, Ref, StringError,
+/// # };
+/// # fn main() -> Result<(), StringError> {
+/// let root = (1u8.wrap_as::<Ref<Data>>(), 2u8.wrap_as::<Ref<Data>>()).to_cell(((), ()))?;
+/// let packed = BagOfCells::from_root(root).serialize(BagOfCellsArgs::default())?;
+///
+/// let view = BocView::new(&packed)?;
+/// let second = view.single_root()?.reference(1)?;
+/// assert_eq!(second.data().unpack::<u8>(())?, 2);
+/// assert_eq!(second.parser()?.parse_as::<u8, Data>(())?, 2);
+/// # Ok(())
+/// # }
+/// ```
+pub struct BocView<'a> {
+    /// `cell_data` section
+    data: &'a [u8],
+    /// Start offsets of cells in `data` followed by its total length
+    offsets: Vec<usize>,
+    size_bytes: usize,
+    roots: Vec<u32>,
+    limits: BocLimits,
+    loaded: Vec<OnceCell<Arc<Cell>>>,
+    /// Cells being loaded at the moment, used to detect cycles
+    loading: Vec<cell::Cell<bool>>,
+}
+
+impl<'a> BocView<'a> {
+    /// Parse header of serialized BoC and locate its cells
+    #[inline]
+    pub fn new(bytes: &'a [u8]) -> Result<Self, StringError> {
+        Self::new_limited(bytes, BocLimits::default()).map_err(Error::custom)
+    }
+
+    /// Same as [`.new()`](BocView::new), but for bytes of untrusted
+    /// origin, see [`BagOfCells::deserialize_limited`](super::BagOfCells::deserialize_limited).
+    ///
+    /// Limits of the header are checked here, while [`max_cell_bits`](BocLimits::max_cell_bits)
+    /// and [`max_depth`](BocLimits::max_depth) are checked when cells are
+    /// accessed and loaded respectively.
+    pub fn new_limited(bytes: &'a [u8], limits: BocLimits) -> Result<Self, BocError> {
+        let mut reader = bytes.as_bits();
+        let BocHeader {
+            has_crc32c,
+            has_cache_bits,
+            size_bytes,
+            cells,
+            tot_cells_size,
+            roots,
+            index,
+            ..
+        } = BocHeader::unpack_limited(&mut (&mut reader).map_err(BocError::custom), limits)?;
+        let start = bytes.len() - reader.len().div_ceil(8);
+        let data = usize::try_from(tot_cells_size)
+            .ok()
+            .and_then(|len| bytes.get(start..start.checked_add(len)?))
+            .ok_or_else(|| Error::custom("cell_data: EOF"))?;
+        let end = start + data.len();
+
+        if has_crc32c {
+            // crc32c:has_crc32c?uint32
+            let cs: [u8; 4] = bytes
+                .get(end..end + 4)
+                .and_then(|cs| cs.try_into().ok())
+                .ok_or_else(|| Error::custom("crc32c: EOF"))?;
+            if u32::from_le_bytes(cs) != CRC_32_ISCSI.checksum(&bytes[..end]) {
+                return Err(Error::custom("CRC mismatch"));
+            }
+        }
+
+        let size_bytes = size_bytes as usize;
+        let offsets: Vec<usize> = match index {
+            Some(index) => iter::once(0)
+                .chain(
+                    index
+                        .into_iter()
+                        .map(|offset| if has_cache_bits { offset >> 1 } else { offset })
+                        .map(|offset| offset as usize),
+                )
+                .collect(),
+            None => {
+                // `cells` is not validated yet, while each cell takes at
+                // least 2 bytes
+                let mut offsets = Vec::with_capacity((cells as usize).min(data.len() / 2) + 1);
+                let mut offset = 0;
+                for i in 0..cells as usize {
+                    offsets.push(offset);
+                    offset += data
+                        .get(offset..)
+                        .and_then(|cell| cell_size(cell, size_bytes))
+                        .ok_or_else(|| Error::custom(format!("[{i}]: EOF")))?;
+                }
+                offsets.push(offset);
+                offsets
+            }
+        };
+        if !offsets.is_sorted() || offsets.last() != Some(&data.len()) {
+            return Err(Error::custom("cell offsets do not match tot_cells_size"));
+        }
+        if let Some(r) = roots.iter().find(|r| **r >= cells) {
+            return Err(Error::custom(format!("root out of bounds: [{r}]")));
+        }
+
+        Ok(Self {
+            data,
+            offsets,
+            size_bytes,
+            roots,
+            limits,
+            loaded: iter::repeat_with(OnceCell::new)
+                .take(cells as usize)
+                .collect(),
+            loading: iter::repeat_with(Default::default)
+                .take(cells as usize)
+                .collect(),
+        })
+    }
+
+    /// Number of cells
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.loaded.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of roots
+    #[inline]
+    pub fn roots_len(&self) -> usize {
+        self.roots.len()
+    }
+
+    /// Return `i`-th root
+    #[inline]
+    pub fn root(&self, i: usize) -> Result<CellView<'_, 'a>, StringError> {
+        let index = *self
+            .roots
+            .get(i)
+            .ok_or_else(|| Error::custom(format!("root out of bounds: {i}")))?;
+        self.cell(index as usize)
+    }
+
+    /// Return single root or error otherwise
+    #[inline]
+    pub fn single_root(&self) -> Result<CellView<'_, 'a>, StringError> {
+        if self.roots_len() != 1 {
+            return Err(Error::custom(format!(
+                "expected single root, got: {}",
+                self.roots_len()
+            )));
+        }
+        self.root(0)
+    }
+
+    /// Decode descriptors of cell with given index in the BoC
+    pub fn cell(&self, index: usize) -> Result<CellView<'_, 'a>, StringError> {
+        if index >= self.len() {
+            return Err(Error::custom(format!("cell out of bounds: [{index}]")));
+        }
+        let bytes = self
+            .data
+            .get(self.offsets[index]..self.offsets[index + 1])
+            .ok_or_else(|| Error::custom(format!("[{index}]: invalid offset")))?;
+        let view = CellView {
+            boc: self,
+            index,
+            bytes,
+        };
+        view.validate().with_context(|| format!("[{index}]"))?;
+        if view.data().len() > self.limits.max_cell_bits {
+            return Err(Error::custom(BocError::CellTooLong {
+                index,
+                max: self.limits.max_cell_bits,
+                got: view.data().len(),
+            }));
+        }
+        Ok(view)
+    }
+
+    /// Load cell at given index with its whole subtree. Cells are built
+    /// in post-order using an explicit stack, so that deep trees do not
+    /// overflow the call stack.
+    fn load(&self, index: usize) -> Result<&Arc<Cell>, StringError> {
+        // (index, whether its references have been pushed already)
+        let mut stack = vec![(index, false)];
+        if let Err(err) = self.load_in(&mut stack) {
+            for (i, _) in stack.into_iter().filter(|(_, expanded)| *expanded) {
+                self.loading[i].set(false);
+            }
+            return Err(err);
+        }
+        Ok(self.loaded[index]
+            .get()
+            .unwrap_or_else(|| unreachable!("[{index}]: not loaded")))
+    }
+
+    fn load_in(&self, stack: &mut Vec<(usize, bool)>) -> Result<(), StringError> {
+        while let Some((index, expanded)) = stack.last_mut() {
+            let index = *index;
+            if *expanded {
+                stack.pop();
+                let cell = self.build(index);
+                self.loading[index].set(false);
+                let cell = cell?;
+                self.loaded[index].get_or_init(|| cell.into());
+                continue;
+            }
+            if self.loaded[index].get().is_some() {
+                stack.pop();
+                continue;
+            }
+            // cells being loaded are the ancestors of the current one
+            if self.loading[index].replace(true) {
+                return Err(Error::custom(format!("[{index}]: cycle detected")));
+            }
+            *expanded = true;
+            for r in self.cell(index)?.references() {
+                let r = r?;
+                if self.loaded[r.index].get().is_none() {
+                    stack.push((r.index, false));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Construct cell at given index, its references should be loaded
+    /// already
+    fn build(&self, index: usize) -> Result<Cell, StringError> {
+        let view = self.cell(index)?;
+        if view.is_absent() {
+            return Err(Error::custom(format!("[{index}]: absent cell")));
+        }
+        let references = view
+            .references()
+            .map(|r| {
+                let r = r?;
+                self.loaded[r.index]
+                    .get()
+                    .cloned()
+                    .ok_or_else(|| Error::custom(format!("[{}]: not loaded", r.index)))
+            })
+            .collect::<Result<_, _>>()?;
+        let cell = Cell::from_parts(view.is_exotic(), view.data().to_bitvec(), references)
+            .with_context(|| format!("[{index}]"))?;
+        if cell.max_depth() > self.limits.max_depth {
+            return Err(Error::custom(BocError::TooDeep {
+                index,
+                max: self.limits.max_depth,
+                got: cell.max_depth(),
+            }));
+        }
+        if cell.level_mask() != view.level_mask() {
+            return Err(Error::custom(format!(
+                "[{index}]: level mask mismatch: stored {:?}, actual {:?}",
+                view.level_mask(),
+                cell.level_mask(),
+            )));
+        }
+        Ok(cell)
+    }
+}
+
+/// Size of serialized cell, or `None` if `bytes` are too short to
+/// contain its descriptors
+fn cell_size(bytes: &[u8], size_bytes: usize) -> Option<usize> {
+    let (d1, d2) = (*bytes.first()?, *bytes.get(1)?);
+    let hashes_len = if d1 & 0b10000 != 0 {
+        LevelMask::new(d1 >> 5).hashes_count() * (32 + 2)
+    } else {
+        0
+    };
+    // absent cells have no references
+    let refs = match (d1 & 0b111) as usize {
+        7 => 0,
+        refs => refs,
+    };
+    Some(2 + hashes_len + (d2 as usize).div_ceil(2) + refs * size_bytes)
+}
+
+/// Cell of [`BocView`] decoded on demand
+#[derive(Clone, Copy)]
+pub struct CellView<'v, 'a> {
+    boc: &'v BocView<'a>,
+    index: usize,
+    /// Serialized cell
+    bytes: &'a [u8],
+}
+
+impl<'v, 'a> CellView<'v, 'a> {
+    fn validate(&self) -> Result<(), StringError> {
+        if cell_size(self.bytes, self.boc.size_bytes) != Some(self.bytes.len()) {
+            return Err(Error::custom("invalid cell size"));
+        }
+        let refs = self.d1() & 0b111;
+        if refs > 4 && !self.is_absent() {
+            return Err(Error::custom(format!("invalid refs number: {refs}")));
+        }
+        if self.is_absent() && self.hashes_len() == 0 {
+            return Err(Error::custom("absent cell without hashes"));
+        }
+        if !self.is_absent() && self.bytes[1] & 1 == 1 {
+            let last = self.bytes[self.data_range().end - 1];
+            if last == 0 {
+                return Err(Error::custom("last byte must be non zero"));
+            }
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn d1(&self) -> u8 {
+        self.bytes[0]
+    }
+
+    fn hashes_len(&self) -> usize {
+        if self.d1() & 0b10000 == 0 {
+            return 0;
+        }
+        self.level_mask().hashes_count() * (32 + 2)
+    }
+
+    fn data_range(&self) -> core::ops::Range<usize> {
+        let start = 2 + self.hashes_len();
+        start..start + (self.bytes[1] as usize).div_ceil(2)
+    }
+
+    /// Index of this cell in the BoC
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns whether this cell is absent from the BoC, i.e. only its
+    /// hashes are stored
+    #[inline]
+    pub fn is_absent(&self) -> bool {
+        self.d1() & 0b111 == 7
+    }
+
+    #[inline]
+    pub fn is_exotic(&self) -> bool {
+        self.d1() & 0b1000 != 0
+    }
+
+    /// Stored level mask
+    #[inline]
+    pub fn level_mask(&self) -> LevelMask {
+        LevelMask::new(self.d1() >> 5)
+    }
+
+    /// Data bits borrowed from underlying bytes
+    pub fn data(&self) -> &'a BitSlice<u8, Msb0> {
+        if self.is_absent() {
+            return BitSlice::empty();
+        }
+        let data = self.bytes[self.data_range()].view_bits::<Msb0>();
+        if self.bytes[1] & 1 == 0 {
+            return data;
+        }
+        // strip completion tag
+        &data[..data.len() - data.trailing_zeros() - 1]
+    }
+
+    /// Number of references
+    #[inline]
+    pub fn references_len(&self) -> usize {
+        if self.is_absent() {
+            return 0;
+        }
+        (self.d1() & 0b111) as usize
+    }
+
+    /// Decode `i`-th reference
+    pub fn reference(&self, i: usize) -> Result<CellView<'v, 'a>, StringError> {
+        if i >= self.references_len() {
+            return Err(Error::custom(format!("reference out of bounds: {i}")));
+        }
+        let size_bytes = self.boc.size_bytes;
+        let start = self.data_range().end + i * size_bytes;
+        let index = self.bytes[start..start + size_bytes]
+            .iter()
+            .fold(0, |index, b| index << 8 | *b as usize);
+        self.boc.cell(index)
+    }
+
+    /// Decode all references
+    #[inline]
+    pub fn references(
+        &self,
+    ) -> impl ExactSizeIterator<Item = Result<CellView<'v, 'a>, StringError>> + '_ {
+        (0..self.references_len()).map(|i| self.reference(i))
+    }
+
+    /// Load this cell with its whole subtree. Loaded cells are cached in
+    /// [`BocView`], so each of them is built only once.
+    #[inline]
+    pub fn load(&self) -> Result<&'v Arc<Cell>, StringError> {
+        self.boc.load(self.index)
+    }
+
+    /// Return [`CellParser`] for this cell, which decodes references
+    /// only when they are parsed, so that parsing a single path in a
+    /// large BoC costs only the cells on that path.
+    ///
+    /// Unlike [`.load()`](CellView::load), it does not verify hashes,
+    /// level masks and layouts of exotic cells, unless they are parsed
+    /// into [`Cell`].
+    #[inline]
+    pub fn parser(&self) -> Result<CellParser<'v>, StringError> {
+        if self.is_absent() {
+            return Err(Error::custom(format!("[{}]: absent cell", self.index)));
+        }
+        CellParser::view(*self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        BagOfCells, BagOfCellsArgs,
+        r#as::{Data, Ref},
+        ser::{CellSerializeExt, CellSerializeWrapAsExt},
+    };
+
+    use super::*;
+
+    /// ```text
+    /// root -> { a -> { c }, b -> { c } }
+    /// ```
+    fn tree() -> Cell {
+        let c: Arc<Cell> = [0xC_u8; 3]
+            .wrap_as::<Data>()
+            .to_cell(())
+            .map(Arc::new)
+            .unwrap();
+        let node = |v: u8| {
+            (
+                v.wrap_as::<Data>(),
+                true.wrap_as::<Data>(),
+                c.wrap_as::<Ref>(),
+            )
+                .to_cell(((), (), ()))
+                .unwrap()
+        };
+        (node(0xA).wrap_as::<Ref>(), node(0xB).wrap_as::<Ref>())
+            .to_cell(((), ()))
+            .unwrap()
+    }
+
+    fn assert_same(view: CellView, cell: &Cell) {
+        assert_eq!(view.data(), cell.data());
+        assert_eq!(view.is_exotic(), cell.is_exotic());
+        assert_eq!(view.level_mask(), cell.level_mask());
+        assert_eq!(view.references_len(), cell.references().len());
+        for (r, c) in view.references().zip(cell.references()) {
+            assert_same(r.unwrap(), c);
+        }
+    }
+
+    #[test]
+    fn view() {
+        let root = tree();
+        let boc = BagOfCells::from_root(root.clone());
+        for has_idx in [false, true] {
+            let packed = boc
+                .serialize(BagOfCellsArgs {
+                    has_idx,
+                    has_crc32c: true,
+                    has_cache_bits: has_idx,
+                    ..Default::default()
+                })
+                .unwrap();
+            let view = BocView::new(&packed).unwrap();
+            assert_eq!(view.len(), 4);
+
+            let view_root = view.single_root().unwrap();
+            assert_same(view_root, &root);
+            assert_eq!(**view_root.load().unwrap(), root);
+
+            let a = view_root.reference(0).unwrap();
+            let (v, bit): (u8, bool) = a
+                .parser()
+                .unwrap()
+                .parse_as::<_, (Data, Data)>(((), ()))
+                .unwrap();
+            assert_eq!((v, bit), (0xA, true));
+        }
+    }
+
+    #[test]
+    fn huge_cells_count() {
+        let mut bytes = hex::decode("b5ee9c720401ffffffff000000010000000000000000").unwrap();
+        assert!(BocView::new(&bytes).is_err());
+
+        // index of 2^32 - 1 entries
+        bytes[4] |= 0x80;
+        assert!(BocView::new(&bytes).is_err());
+    }
+
+    #[test]
+    fn limits() {
+        let packed = BagOfCells::from_root(tree())
+            .serialize(BagOfCellsArgs::default())
+            .unwrap();
+        assert!(matches!(
+            BocView::new_limited(
+                &packed,
+                BocLimits {
+                    max_cells: 3,
+                    ..Default::default()
+                }
+            ),
+            Err(BocError::TooManyCells { max: 3, got: 4 })
+        ));
+
+        let view = BocView::new_limited(
+            &packed,
+            BocLimits {
+                max_depth: 1,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let root = view.single_root().unwrap();
+        assert!(root.reference(0).unwrap().load().is_ok());
+        assert!(root.load().unwrap_err().to_string().contains("too deep"));
+
+        let view = BocView::new_limited(
+            &packed,
+            BocLimits {
+                max_cell_bits: 16,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let a = view.single_root().unwrap().reference(0).unwrap();
+        assert!(
+            a.reference(0)
+                .is_err_and(|err| err.to_string().contains("too long"))
+        );
+    }
+
+    #[test]
+    fn absent() {
+        let root = tree();
+        let a = root.references()[0].clone();
+        let mut boc = BagOfCells::from_root(root);
+        boc.add_absent(a.hash());
+        for has_idx in [false, true] {
+            let packed = boc
+                .serialize(BagOfCellsArgs {
+                    has_idx,
+                    ..Default::default()
+                })
+                .unwrap();
+
+            let view = BocView::new(&packed).unwrap();
+            let absent = view.single_root().unwrap().reference(0).unwrap();
+            assert!(absent.is_absent());
+            assert!(absent.data().is_empty());
+            assert_eq!(absent.references_len(), 0);
+            assert!(absent.load().is_err());
+            assert!(absent.parser().is_err());
+        }
+    }
+
+    #[test]
+    fn deep_chain() {
+        use crate::boc::RawBagOfCells;
+
+        let packed = RawBagOfCells::chain(Cell::MAX_DEPTH as u32 + 1);
+        let view = BocView::new(&packed).unwrap();
+        let root = view.single_root().unwrap();
+        assert_eq!(root.load().unwrap().max_depth(), Cell::MAX_DEPTH);
+
+        // would overflow the stack if loaded recursively
+        let packed = RawBagOfCells::chain(100_000);
+        let view = BocView::new(&packed).unwrap();
+        let root = view.single_root().unwrap();
+        assert!(root.load().is_err());
+        assert!(root.load().is_err());
+    }
+
+    #[test]
+    fn lazy_parser() {
+        let root = tree();
+        let mut packed = BagOfCells::from_root(root)
+            .serialize(BagOfCellsArgs::default())
+            .unwrap();
+        let view = BocView::new(&packed).unwrap();
+        let b = view.single_root().unwrap().reference(1).unwrap().index();
+        let offset = view.data.as_ptr() as usize - packed.as_ptr() as usize + view.offsets[b];
+        // mark `b` as exotic with unknown type
+        packed[offset] |= 0b1000;
+
+        let view = BocView::new(&packed).unwrap();
+        let mut parser = view.single_root().unwrap().parser().unwrap();
+        let a: (u8, bool, [u8; 3]) = parser
+            .parse_as::<_, Ref<(Data, Data, Ref<Data>)>>(((), (), ()))
+            .unwrap();
+        assert_eq!(a, (0xA, true, [0xC; 3]));
+        let err = parser.parse_as::<Cell, Ref>(()).unwrap_err();
+        assert!(
+            err.to_string().contains("unknown exotic cell type"),
+            "{err}"
+        );
+
+        // references are loaded when parsed into `Cell`
+        let mut parser = view.single_root().unwrap().parser().unwrap();
+        let a: Arc<Cell> = parser.parse_as::<_, Ref>(()).unwrap();
+        assert_eq!(
+            a,
+            *view
+                .single_root()
+                .unwrap()
+                .reference(0)
+                .unwrap()
+                .load()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn lazy() {
+        let root = tree();
+        let mut packed = BagOfCells::from_root(root)
+            .serialize(BagOfCellsArgs::default())
+            .unwrap();
+
+        let view = BocView::new(&packed).unwrap();
+        let b = view.single_root().unwrap().reference(1).unwrap().index();
+        let offset = view.data.as_ptr() as usize - packed.as_ptr() as usize + view.offsets[b];
+        // mark `b` as exotic with invalid layout
+        packed[offset] |= 0b1000;
+        assert!(BagOfCells::deserialize(&packed).is_err());
+
+        let view = BocView::new(&packed).unwrap();
+        let root = view.single_root().unwrap();
+        let a = root.reference(0).unwrap();
+        assert!(a.load().is_ok());
+        assert!(root.reference(1).unwrap().load().is_err());
+        assert!(root.load().is_err());
+    }
+}
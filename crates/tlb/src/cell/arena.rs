@@ -0,0 +1,204 @@
+use core::fmt::{self, Debug};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use super::Cell;
+
+/// Interner canonicalizing [`Cell`]s by their representation
+/// [hashes](Cell::hash), so that equal subtrees share a single allocation.
+///
+/// It is a cheap handle, which can be cloned and shared between threads:
+/// all clones refer to the same set of cells. Interned cells are kept
+/// alive until [`.clear()`](CellArena::clear) is called or all handles
+/// are dropped.
+///
+/// Cells can be interned directly or on creation with
+/// [`Cell::builder_in()`], [`.to_cell_in()`](crate::ser::CellSerializeExt::to_cell_in)
+/// and [`BagOfCells::deserialize_in()`](crate::BagOfCells::deserialize_in).
+/// ```rust
+/// # use std::sync::Arc;
+/// # use tlb::{
+/// #     ser::{CellSerializeExt, CellSerializeWrapAsExt},
+/// #     CellArena, Data, Ref,
+/// # };
+/// let arena = CellArena::new();
+/// let code = 0xC0DE_u16.wrap_as::<Data>();
+///
+/// let a = (1u8.wrap_as::<Data>(), code.wrap_as::<Ref>())
+///     .to_cell_in(((), ()), &arena)
+///     .unwrap();
+/// let b = (2u8.wrap_as::<Data>(), code.wrap_as::<Ref>())
+///     .to_cell_in(((), ()), &arena)
+///     .unwrap();
+///
+/// assert!(Arc::ptr_eq(&a.references()[0], &b.references()[0]));
+/// assert_eq!(arena.len(), 3);
+/// ```
+#[derive(Clone, Default)]
+pub struct CellArena(Arc<Mutex<HashMap<[u8; 32], Arc<Cell>>>>);
+
+impl CellArena {
+    /// Create empty arena
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    fn cells(&self) -> MutexGuard<'_, HashMap<[u8; 32], Arc<Cell>>> {
+        // cells are inserted atomically, so the map is never left
+        // in inconsistent state
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Return canonical instance of given cell.
+    ///
+    /// If an equal cell was interned before, then it is returned.
+    /// Otherwise, references of the cell are interned recursively and
+    /// the cell becomes canonical itself.
+    pub fn intern(&self, cell: impl Into<Arc<Cell>>) -> Arc<Cell> {
+        Self::intern_in(&mut self.cells(), cell.into())
+    }
+
+    fn intern_in(cells: &mut HashMap<[u8; 32], Arc<Cell>>, cell: Arc<Cell>) -> Arc<Cell> {
+        let hash = Cell::hash(&cell);
+        if let Some(canonical) = cells.get(&hash) {
+            return canonical.clone();
+        }
+        let references: Vec<Arc<Cell>> = cell
+            .references
+            .iter()
+            .map(|r| Self::intern_in(cells, r.clone()))
+            .collect();
+        let cell = if references
+            .iter()
+            .zip(&cell.references)
+            .all(|(canonical, r)| Arc::ptr_eq(canonical, r))
+        {
+            cell
+        } else {
+            // same contents, so hashes and depths remain valid
+            Arc::new(Cell {
+                r#type: cell.r#type,
+                data: cell.data.clone(),
+                references,
+                level_mask: cell.level_mask,
+                hashes: cell.hashes,
+                depths: cell.depths,
+            })
+        };
+        cells.insert(hash, cell.clone());
+        cell
+    }
+
+    /// Return interned cell with given representation hash
+    #[inline]
+    pub fn get(&self, hash: &[u8; 32]) -> Option<Arc<Cell>> {
+        self.cells().get(hash).cloned()
+    }
+
+    /// Number of interned cells
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.cells().len()
+    }
+
+    /// Returns whether no cells were interned
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.cells().is_empty()
+    }
+
+    /// Release all interned cells
+    #[inline]
+    pub fn clear(&self) {
+        self.cells().clear()
+    }
+}
+
+impl Debug for CellArena {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CellArena")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        BagOfCells,
+        r#as::{Data, Ref},
+        ser::{CellSerializeExt, CellSerializeWrapAsExt},
+    };
+
+    use super::*;
+
+    /// ```text
+    /// root -> { a -> { leaf }, b -> { leaf } }
+    /// ```
+    fn tree() -> Cell {
+        // each subtree is allocated separately
+        let leaf = || 0xFF_u8.wrap_as::<Data>().to_cell(()).unwrap();
+        let node = |v: u8| {
+            (v.wrap_as::<Data>(), leaf().wrap_as::<Ref>())
+                .to_cell(((), ()))
+                .unwrap()
+        };
+        (node(0xA).wrap_as::<Ref>(), node(0xB).wrap_as::<Ref>())
+            .to_cell(((), ()))
+            .unwrap()
+    }
+
+    #[test]
+    fn intern() {
+        let arena = CellArena::new();
+        let root = arena.intern(tree());
+        assert_eq!(arena.len(), 4);
+        assert!(Arc::ptr_eq(
+            &root.references()[0].references()[0],
+            &root.references()[1].references()[0],
+        ));
+
+        assert!(Arc::ptr_eq(&arena.intern(tree()), &root));
+        assert_eq!(arena.len(), 4);
+        assert!(Arc::ptr_eq(&arena.get(&Cell::hash(&root)).unwrap(), &root));
+
+        arena.clear();
+        assert!(arena.is_empty());
+    }
+
+    #[test]
+    fn builder() {
+        let arena = CellArena::new();
+        let leaf = arena.intern(0xFF_u8.wrap_as::<Data>().to_cell(()).unwrap());
+
+        let mut builder = Cell::builder_in(&arena);
+        builder
+            .store_as::<_, Ref<Data>>(0xFF_u8, ())
+            .unwrap()
+            .store_as::<_, Ref<Ref<Data>>>(0xFF_u8, ())
+            .unwrap();
+        let cell = builder.into_cell().unwrap();
+
+        assert!(Arc::ptr_eq(&cell.references()[0], &leaf));
+        assert!(Arc::ptr_eq(&cell.references()[1].references()[0], &leaf));
+        assert_eq!(arena.len(), 2);
+    }
+
+    #[test]
+    fn boc() {
+        let arena = CellArena::new();
+        let root = arena.intern(tree());
+        let packed = BagOfCells::from_root(tree())
+            .serialize(Default::default())
+            .unwrap();
+
+        let boc = BagOfCells::deserialize_in(&packed, &arena).unwrap();
+        assert!(Arc::ptr_eq(boc.single_root().unwrap(), &root));
+        assert_eq!(arena.len(), 4);
+    }
+}
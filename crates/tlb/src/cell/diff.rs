@@ -0,0 +1,308 @@
+use core::{
+    fmt::{self, Display},
+    ops::Range,
+};
+use std::{collections::HashSet, sync::Arc};
+
+use super::{Cell, CellType};
+
+/// Compare two cell trees and return changes turning `old` into `new`.
+///
+/// Subtrees with equal representation [hashes](Cell::hash) are skipped
+/// without being traversed, so the cost is proportional to the size of
+/// the changed part. Each pair of differing subtrees is compared only
+/// once, so changes of subtrees shared by several parents are reported
+/// only at the first path leading to them.
+///
+/// Use [`Display`] implementation of [`CellDiff`] to render it in
+/// human-readable form:
+/// ```rust
+/// # use tlb::{
+/// #     ser::{CellSerializeExt, CellSerializeWrapAsExt},
+/// #     diff, Data, Ref,
+/// # };
+/// let old = (1u8.wrap_as::<Data>(), 0xAB_u8.wrap_as::<Ref<Data>>())
+///     .to_cell(((), ()))
+///     .unwrap();
+/// let new = (1u8.wrap_as::<Data>(), 0xAC_u8.wrap_as::<Ref<Data>>())
+///     .to_cell(((), ()))
+///     .unwrap();
+///
+/// assert_eq!(
+///     diff(&old, &new).to_string(),
+///     "/0 ~ 8[0xAB] -> 8[0xAC], bits: 5..8\n",
+/// );
+/// ```
+pub fn diff(old: &Cell, new: &Cell) -> CellDiff {
+    let mut changes = Vec::new();
+    if Cell::hash(old) != Cell::hash(new) {
+        diff_at(
+            &Arc::new(old.clone()),
+            &Arc::new(new.clone()),
+            &mut Vec::new(),
+            &mut HashSet::new(),
+            &mut changes,
+        );
+    }
+    CellDiff { changes }
+}
+
+fn diff_at(
+    old: &Arc<Cell>,
+    new: &Arc<Cell>,
+    path: &mut Vec<usize>,
+    seen: &mut HashSet<([u8; 32], [u8; 32])>,
+    changes: &mut Vec<CellChange>,
+) {
+    if !seen.insert((Cell::hash(old), Cell::hash(new))) {
+        return;
+    }
+    let bits = diff_bits(old, new);
+    if old.cell_type() != new.cell_type() || !bits.is_empty() {
+        changes.push(CellChange {
+            path: path.clone(),
+            kind: CellChangeKind::Modified {
+                old: old.clone(),
+                new: new.clone(),
+                bits,
+            },
+        });
+    }
+    let (old_refs, new_refs) = (old.references(), new.references());
+    for i in 0..old_refs.len().max(new_refs.len()) {
+        path.push(i);
+        match (old_refs.get(i), new_refs.get(i)) {
+            (Some(o), Some(n)) if Cell::hash(o) != Cell::hash(n) => {
+                diff_at(o, n, path, seen, changes);
+            }
+            (Some(o), None) => changes.push(CellChange {
+                path: path.clone(),
+                kind: CellChangeKind::Removed(o.clone()),
+            }),
+            (None, Some(n)) => changes.push(CellChange {
+                path: path.clone(),
+                kind: CellChangeKind::Added(n.clone()),
+            }),
+            _ => {}
+        }
+        path.pop();
+    }
+}
+
+/// Ranges of differing bits, where bits present only in one of cells
+/// are treated as different
+fn diff_bits(old: &Cell, new: &Cell) -> Vec<Range<usize>> {
+    let (old, new) = (old.data(), new.data());
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for i in 0..old.len().max(new.len()) {
+        if old.get(i).as_deref() == new.get(i).as_deref() {
+            continue;
+        }
+        match ranges.last_mut() {
+            Some(last) if last.end == i => last.end += 1,
+            _ => ranges.push(i..i + 1),
+        }
+    }
+    ranges
+}
+
+/// Result of [`diff()`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CellDiff {
+    changes: Vec<CellChange>,
+}
+
+impl CellDiff {
+    /// Returns whether trees are equal
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Changes in pre-order of their paths
+    #[inline]
+    pub fn changes(&self) -> &[CellChange] {
+        &self.changes
+    }
+
+    #[inline]
+    pub fn into_changes(self) -> Vec<CellChange> {
+        self.changes
+    }
+}
+
+impl Display for CellDiff {
+    /// Renders each change on its own line
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{change}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Change of a single cell in [`CellDiff`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellChange {
+    /// Indices of references leading from the root to the changed cell
+    pub path: Vec<usize>,
+    pub kind: CellChangeKind,
+}
+
+impl Display for CellChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "/")?;
+        }
+        for i in &self.path {
+            write!(f, "/{i}")?;
+        }
+        match &self.kind {
+            CellChangeKind::Added(cell) => write!(f, " + {}", Summary(cell)),
+            CellChangeKind::Removed(cell) => write!(f, " - {}", Summary(cell)),
+            CellChangeKind::Modified { old, new, bits } => {
+                write!(f, " ~ {} -> {}", Summary(old), Summary(new))?;
+                if !bits.is_empty() {
+                    write!(f, ", bits: ")?;
+                    for (i, r) in bits.iter().enumerate() {
+                        if i > 0 {
+                            write!(f, ", ")?;
+                        }
+                        write!(f, "{r:?}")?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Kind of [`CellChange`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CellChangeKind {
+    /// Reference is present only in the new tree
+    Added(Arc<Cell>),
+    /// Reference is present only in the old tree
+    Removed(Arc<Cell>),
+    /// [Type](Cell::cell_type) or data of the cell changed, while changes
+    /// of its references are reported separately
+    Modified {
+        old: Arc<Cell>,
+        new: Arc<Cell>,
+        /// Ranges of differing data bits, see [`diff()`]
+        bits: Vec<Range<usize>>,
+    },
+}
+
+/// Cell without its subtree
+struct Summary<'a>(&'a Cell);
+
+impl Display for Summary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cell = self.0;
+        if cell.cell_type() != CellType::Ordinary {
+            write!(f, "{:?} ", cell.cell_type())?;
+        }
+        let (bits_len, data) = cell.data_bytes();
+        write!(f, "{}[0x{}]", bits_len, hex::encode_upper(data))?;
+        match cell.references().len() {
+            0 => Ok(()),
+            1 => write!(f, " -> 1 reference"),
+            n => write!(f, " -> {n} references"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        r#as::{Data, Ref},
+        ser::{CellSerializeExt, CellSerializeWrapAsExt},
+    };
+
+    use super::*;
+
+    fn node(v: u16, children: &[Arc<Cell>]) -> Arc<Cell> {
+        let mut builder = Cell::builder();
+        builder.store_as::<_, Data>(v, ()).unwrap();
+        for c in children {
+            builder.store_as::<_, Ref>(c, ()).unwrap();
+        }
+        builder.into_cell().unwrap().into()
+    }
+
+    #[test]
+    fn equal() {
+        let a = node(1, &[node(2, &[])]);
+        assert!(diff(&a, &node(1, &[node(2, &[])])).is_empty());
+    }
+
+    #[test]
+    fn changes() {
+        let shared = node(0xFFFF, &[node(0xEEEE, &[])]);
+        let old = node(
+            0x1234,
+            &[shared.clone(), node(0xA, &[]), node(0xB, &[node(0xC, &[])])],
+        );
+        let new = node(0x1234, &[shared.clone(), node(0xA0, &[])]);
+
+        let d = diff(&old, &new);
+        assert_eq!(
+            d.changes(),
+            [
+                CellChange {
+                    path: [1].into(),
+                    kind: CellChangeKind::Modified {
+                        old: node(0xA, &[]),
+                        new: node(0xA0, &[]),
+                        bits: [8..9, 10..11, 12..13, 14..15].into(),
+                    },
+                },
+                CellChange {
+                    path: [2].into(),
+                    kind: CellChangeKind::Removed(node(0xB, &[node(0xC, &[])])),
+                },
+            ]
+        );
+        assert_eq!(
+            d.to_string(),
+            "/1 ~ 16[0x000A] -> 16[0x00A0], bits: 8..9, 10..11, 12..13, 14..15\n\
+             /2 - 16[0x000B] -> 1 reference\n",
+        );
+
+        let d = diff(&new, &old);
+        assert_eq!(d.changes()[1].to_string(), "/2 + 16[0x000B] -> 1 reference");
+    }
+
+    #[test]
+    fn shared_subtrees() {
+        // each node references the same child twice, so there are 2^64
+        // paths to the leaf
+        let chain =
+            |leaf: u16| (0..64).fold(node(leaf, &[]), |child, i| node(i, &[child.clone(), child]));
+        let d = diff(&chain(0xA), &chain(0x3));
+        assert_eq!(
+            d.changes(),
+            [CellChange {
+                path: [0; 64].into(),
+                kind: CellChangeKind::Modified {
+                    old: node(0xA, &[]),
+                    new: node(0x3, &[]),
+                    bits: [12..13, 15..16].into(),
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn root_data() {
+        let old = 0b101_u8.wrap_as::<Data>().to_cell(()).unwrap();
+        let new = (0b101_u8.wrap_as::<Data>(), true.wrap_as::<Data>())
+            .to_cell(((), ()))
+            .unwrap();
+        assert_eq!(
+            diff(&old, &new).to_string(),
+            "/ ~ 8[0x05] -> 9[0x0580], bits: 8..9\n",
+        );
+    }
+}
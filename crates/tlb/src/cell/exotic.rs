@@ -0,0 +1,325 @@
+use std::sync::Arc;
+
+use crate::{
+    Cell, Error, LevelMask, StringError,
+    r#as::Ref,
+    bits::{
+        bitvec::{field::BitField, order::Msb0, slice::BitSlice},
+        de::BitReaderExt,
+        ser::BitWriterExt,
+    },
+    de::{CellDeserialize, CellParser, CellParserError},
+    ser::{CellBuilder, CellBuilderError, CellSerialize},
+};
+
+/// Type of [`Cell`], see [Exotic cells](https://docs.ton.org/develop/data-formats/exotic-cells)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum CellType {
+    #[default]
+    Ordinary,
+    /// See [`PrunedBranch`]
+    PrunedBranch,
+    /// See [`LibraryReference`]
+    LibraryReference,
+    /// See [`MerkleProof`]
+    MerkleProof,
+    /// See [`MerkleUpdate`]
+    MerkleUpdate,
+}
+
+impl CellType {
+    /// Tag stored in the first byte of exotic cell data, `None` for
+    /// [`CellType::Ordinary`]
+    #[inline]
+    #[must_use]
+    pub const fn tag(self) -> Option<u8> {
+        match self {
+            Self::Ordinary => None,
+            Self::PrunedBranch => Some(1),
+            Self::LibraryReference => Some(2),
+            Self::MerkleProof => Some(3),
+            Self::MerkleUpdate => Some(4),
+        }
+    }
+
+    /// Type of exotic cell from its tag
+    #[inline]
+    pub fn from_tag(tag: u8) -> Result<Self, StringError> {
+        Ok(match tag {
+            1 => Self::PrunedBranch,
+            2 => Self::LibraryReference,
+            3 => Self::MerkleProof,
+            4 => Self::MerkleUpdate,
+            tag => {
+                return Err(Error::custom(format!(
+                    "unknown exotic cell type: {tag:#04x}"
+                )));
+            }
+        })
+    }
+
+    /// Type of the cell with given data, where exotic cells start with
+    /// their [tag](CellType::tag)
+    pub(crate) fn of(is_exotic: bool, data: &BitSlice<u8, Msb0>) -> Result<Self, StringError> {
+        if !is_exotic {
+            return Ok(Self::Ordinary);
+        }
+        data.get(..8)
+            .ok_or_else(|| Error::custom("exotic cell must have at least 8 bits"))
+            .and_then(|tag| Self::from_tag(tag.load_be()))
+    }
+
+    /// Returns whether cells of this type are exotic
+    #[inline]
+    #[must_use]
+    pub const fn is_exotic(self) -> bool {
+        !matches!(self, Self::Ordinary)
+    }
+
+    /// Merkle cells look at their children one level higher
+    #[inline]
+    pub(crate) const fn child_level_shift(self) -> u8 {
+        match self {
+            Self::MerkleProof | Self::MerkleUpdate => 1,
+            _ => 0,
+        }
+    }
+
+    #[inline]
+    fn ensure<'de>(self, parser: &mut CellParser<'de>) -> Result<(), CellParserError<'de>> {
+        parser.ensure_exotic()?;
+        let tag: u8 = parser.exotic_data().unpack(())?;
+        let got = Self::from_tag(tag)?;
+        if got != self {
+            return Err(Error::custom(format!("expected {self:?}, got {got:?}")));
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn store(self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder.exotic()?.pack(self.tag().unwrap_or_default(), ())?;
+        Ok(())
+    }
+}
+
+/// [Pruned branch](https://docs.ton.org/develop/data-formats/exotic-cells#pruned-branch)
+/// replaces a subtree while keeping its hashes and depths.
+/// ```tlb
+/// !pruned_branch#01 level_mask:(## 8) hashes:(n * bits256)
+///   depths:(n * uint16) = PrunedBranch;
+/// ```
+/// where `n` is the number of bits set in `level_mask`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PrunedBranch {
+    pub level_mask: LevelMask,
+    pub hashes: Vec<[u8; 32]>,
+    pub depths: Vec<u16>,
+}
+
+impl PrunedBranch {
+    /// Prune given cell so that it can be included in a Merkle proof of
+    /// given `level`, which should be higher than the level of the cell.
+    pub fn new(cell: &Cell, level: u8) -> Result<Self, StringError> {
+        let mask = cell.level_mask();
+        if level <= mask.level() || level > LevelMask::MAX_LEVEL {
+            return Err(Error::custom(format!(
+                "cannot prune cell of level {} at level {level}",
+                mask.level(),
+            )));
+        }
+        let levels = (0..=mask.level()).filter(|l| mask.is_significant(*l));
+        Ok(Self {
+            level_mask: mask | LevelMask::from_level(level),
+            hashes: levels.clone().map(|l| cell.hash_at_level(l)).collect(),
+            depths: levels.map(|l| cell.depth_at_level(l)).collect(),
+        })
+    }
+}
+
+impl CellSerialize for PrunedBranch {
+    type Args = ();
+
+    fn store(&self, builder: &mut CellBuilder, _: Self::Args) -> Result<(), CellBuilderError> {
+        CellType::PrunedBranch.store(builder)?;
+        builder
+            .pack(self.level_mask.mask(), ())?
+            .pack_many(&self.hashes, ())?
+            .pack_many(&self.depths, ())?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for PrunedBranch {
+    type Args = ();
+
+    fn parse(parser: &mut CellParser<'de>, _: Self::Args) -> Result<Self, CellParserError<'de>> {
+        CellType::PrunedBranch.ensure(parser)?;
+        let data = parser.exotic_data();
+        let level_mask = LevelMask::new(data.unpack(())?);
+        let n = level_mask.hash_index();
+        Ok(Self {
+            level_mask,
+            hashes: data.unpack_iter(()).take(n).collect::<Result<_, _>>()?,
+            depths: data.unpack_iter(()).take(n).collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// [Library reference](https://docs.ton.org/develop/data-formats/exotic-cells#library-reference)
+/// ```tlb
+/// !library_ref#02 hash:bits256 = LibraryReference;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LibraryReference {
+    pub hash: [u8; 32],
+}
+
+impl CellSerialize for LibraryReference {
+    type Args = ();
+
+    fn store(&self, builder: &mut CellBuilder, _: Self::Args) -> Result<(), CellBuilderError> {
+        CellType::LibraryReference.store(builder)?;
+        builder.pack(self.hash, ())?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for LibraryReference {
+    type Args = ();
+
+    fn parse(parser: &mut CellParser<'de>, _: Self::Args) -> Result<Self, CellParserError<'de>> {
+        CellType::LibraryReference.ensure(parser)?;
+        Ok(Self {
+            hash: parser.unpack(())?,
+        })
+    }
+}
+
+/// [Merkle proof](https://docs.ton.org/develop/data-formats/exotic-cells#merkle-proof)
+/// ```tlb
+/// !merkle_proof#03 {X:Type} virtual_hash:bits256 depth:uint16 virtual_root:^X = MERKLE_PROOF X;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MerkleProof<T = Arc<Cell>> {
+    pub virtual_hash: [u8; 32],
+    pub depth: u16,
+    pub virtual_root: T,
+}
+
+impl MerkleProof {
+    /// Create Merkle proof for given (possibly pruned) tree
+    #[inline]
+    pub fn new(virtual_root: impl Into<Arc<Cell>>) -> Self {
+        let virtual_root = virtual_root.into();
+        Self {
+            virtual_hash: virtual_root.hash_at_level(0),
+            depth: virtual_root.depth_at_level(0),
+            virtual_root,
+        }
+    }
+}
+
+impl<T> CellSerialize for MerkleProof<T>
+where
+    T: CellSerialize,
+{
+    type Args = T::Args;
+
+    fn store(&self, builder: &mut CellBuilder, args: Self::Args) -> Result<(), CellBuilderError> {
+        CellType::MerkleProof.store(builder)?;
+        builder
+            .pack(self.virtual_hash, ())?
+            .pack(self.depth, ())?
+            .store_as::<_, Ref>(&self.virtual_root, args)?;
+        Ok(())
+    }
+}
+
+impl<'de, T> CellDeserialize<'de> for MerkleProof<T>
+where
+    T: CellDeserialize<'de>,
+{
+    type Args = T::Args;
+
+    fn parse(parser: &mut CellParser<'de>, args: Self::Args) -> Result<Self, CellParserError<'de>> {
+        CellType::MerkleProof.ensure(parser)?;
+        Ok(Self {
+            virtual_hash: parser.unpack(())?,
+            depth: parser.unpack(())?,
+            virtual_root: parser.parse_as::<_, Ref>(args)?,
+        })
+    }
+}
+
+/// [Merkle update](https://docs.ton.org/develop/data-formats/exotic-cells#merkle-update)
+/// ```tlb
+/// !merkle_update#04 {X:Type} from_hash:bits256 to_hash:bits256
+///   from_depth:uint16 to_depth:uint16 old:^X new:^X
+///   = MERKLE_UPDATE X;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MerkleUpdate<T = Arc<Cell>> {
+    pub old_hash: [u8; 32],
+    pub new_hash: [u8; 32],
+    pub old_depth: u16,
+    pub new_depth: u16,
+    pub old: T,
+    pub new: T,
+}
+
+impl MerkleUpdate {
+    /// Create Merkle update between given (possibly pruned) trees
+    #[inline]
+    pub fn new(old: impl Into<Arc<Cell>>, new: impl Into<Arc<Cell>>) -> Self {
+        let (old, new) = (old.into(), new.into());
+        Self {
+            old_hash: old.hash_at_level(0),
+            new_hash: new.hash_at_level(0),
+            old_depth: old.depth_at_level(0),
+            new_depth: new.depth_at_level(0),
+            old,
+            new,
+        }
+    }
+}
+
+impl<T> CellSerialize for MerkleUpdate<T>
+where
+    T: CellSerialize,
+    T::Args: Clone,
+{
+    type Args = T::Args;
+
+    fn store(&self, builder: &mut CellBuilder, args: Self::Args) -> Result<(), CellBuilderError> {
+        CellType::MerkleUpdate.store(builder)?;
+        builder
+            .pack(self.old_hash, ())?
+            .pack(self.new_hash, ())?
+            .pack(self.old_depth, ())?
+            .pack(self.new_depth, ())?
+            .store_as::<_, Ref>(&self.old, args.clone())?
+            .store_as::<_, Ref>(&self.new, args)?;
+        Ok(())
+    }
+}
+
+impl<'de, T> CellDeserialize<'de> for MerkleUpdate<T>
+where
+    T: CellDeserialize<'de>,
+    T::Args: Clone,
+{
+    type Args = T::Args;
+
+    fn parse(parser: &mut CellParser<'de>, args: Self::Args) -> Result<Self, CellParserError<'de>> {
+        CellType::MerkleUpdate.ensure(parser)?;
+        Ok(Self {
+            old_hash: parser.unpack(())?,
+            new_hash: parser.unpack(())?,
+            old_depth: parser.unpack(())?,
+            new_depth: parser.unpack(())?,
+            old: parser.parse_as::<_, Ref>(args.clone())?,
+            new: parser.parse_as::<_, Ref>(args)?,
+        })
+    }
+}
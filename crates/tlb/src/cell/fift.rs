@@ -0,0 +1,184 @@
+use core::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+use std::sync::Arc;
+
+use crate::{
+    Context, Error, StringError,
+    bits::{
+        bitvec::{order::Msb0, vec::BitVec},
+        string::{hex, parse_hex},
+    },
+};
+
+use super::Cell;
+
+/// Fift dump of the cell, as printed by `csr.` or `dump`:
+/// ```text
+/// x{C_}
+///  x{AB}
+///  SPECIAL x{01...}
+/// ```
+/// Each cell is printed on its own line as `x{...}` hex of its data,
+/// where incomplete trailing digit is padded with completion tag and
+/// marked with `_`, followed by its references indented by one more
+/// space. Exotic cells are prefixed with `SPECIAL`.
+///
+/// It can be parsed back with [`FromStr`]:
+/// ```rust
+/// # use tlb::{
+/// #     ser::{CellSerializeExt, CellSerializeWrapAsExt},
+/// #     Cell, Data, Ref,
+/// # };
+/// let cell = (true.wrap_as::<Data>(), 0xAB_u8.wrap_as::<Ref<Data>>())
+///     .to_cell(((), ()))
+///     .unwrap();
+///
+/// let dump = cell.to_string();
+/// assert_eq!(dump, "x{C_}\n x{AB}");
+/// assert_eq!(dump.parse::<Cell>().unwrap(), cell);
+/// ```
+impl Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_fift(f, 0)
+    }
+}
+
+impl Cell {
+    fn fmt_fift(&self, f: &mut fmt::Formatter<'_>, indent: usize) -> fmt::Result {
+        write!(f, "{:indent$}", "")?;
+        if self.is_exotic() {
+            write!(f, "SPECIAL ")?;
+        }
+        write!(f, "x{{{}}}", hex(self.data()))?;
+        for r in self.references() {
+            writeln!(f)?;
+            r.fmt_fift(f, indent + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Cell {
+    type Err = StringError;
+
+    /// Parse Fift dump of the cell, see [`Display`] implementation
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines = s
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| parse_line(i + 1, line).with_context(|| format!("line {}", i + 1)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let base = lines
+            .first()
+            .map(|line| line.indent)
+            .ok_or_else(|| Error::custom("empty dump"))?;
+
+        let mut lines = lines.into_iter().peekable();
+        let cell = build(&mut lines, base)?;
+        if let Some(line) = lines.next() {
+            return Err(Error::custom(format!(
+                "line {}: unexpected indentation",
+                line.number
+            )));
+        }
+        Ok(cell)
+    }
+}
+
+struct Line {
+    number: usize,
+    indent: usize,
+    is_exotic: bool,
+    data: BitVec<u8, Msb0>,
+}
+
+fn parse_line(number: usize, line: &str) -> Result<Line, StringError> {
+    let rest = line.trim_start_matches(' ');
+    let indent = line.len() - rest.len();
+    let rest = rest.trim_end();
+    let (is_exotic, rest) = match rest.strip_prefix("SPECIAL") {
+        Some(rest) => (true, rest.trim_start()),
+        None => (false, rest),
+    };
+    let hex = rest
+        .strip_prefix("x{")
+        .and_then(|rest| rest.strip_suffix('}'))
+        .ok_or_else(|| Error::custom(format!("expected x{{...}}, got: {rest:?}")))?;
+    Ok(Line {
+        number,
+        indent,
+        is_exotic,
+        data: parse_hex(hex)?,
+    })
+}
+
+/// Build cell from the next line and following lines indented deeper
+fn build(
+    lines: &mut core::iter::Peekable<impl Iterator<Item = Line>>,
+    indent: usize,
+) -> Result<Cell, StringError> {
+    let line = lines
+        .next()
+        .ok_or_else(|| Error::custom("unexpected end of dump"))?;
+    let mut references = Vec::new();
+    while lines.peek().is_some_and(|next| next.indent == indent + 1) {
+        references.push(Arc::new(build(lines, indent + 1)?));
+    }
+    Cell::from_parts(line.is_exotic, line.data, references)
+        .with_context(|| format!("line {}", line.number))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        MerkleProof,
+        r#as::{Data, Ref},
+        bits::NBits,
+        ser::{CellSerializeExt, CellSerializeWrapAsExt},
+    };
+
+    use super::*;
+
+    #[test]
+    fn tree() {
+        let leaf = 0b101_u8.wrap_as::<Data<NBits<3>>>().to_cell(()).unwrap();
+        let cell = (
+            0xC0DE_u16.wrap_as::<Data>(),
+            leaf.wrap_as::<Ref>(),
+            (1u8.wrap_as::<Data>(), leaf.wrap_as::<Ref>()).wrap_as::<Ref>(),
+        )
+            .to_cell(((), (), ((), ())))
+            .unwrap();
+
+        let dump = "x{C0DE}\n x{B_}\n x{01}\n  x{B_}";
+        assert_eq!(cell.to_string(), dump);
+        assert_eq!(dump.parse::<Cell>().unwrap(), cell);
+        // trailing newline and common indentation are allowed
+        assert_eq!(
+            "  x{C0DE}\n   x{B_}\n   x{01}\n    x{B_}\n"
+                .parse::<Cell>()
+                .unwrap(),
+            cell
+        );
+
+        assert!("x{C0DE}\n  x{B_}".parse::<Cell>().is_err());
+        assert!("x{C0DE}\nx{B_}".parse::<Cell>().is_err());
+        assert!("".parse::<Cell>().is_err());
+    }
+
+    #[test]
+    fn special() {
+        let cell: Cell = 0xAB_u8.wrap_as::<Data>().to_cell(()).unwrap();
+        let proof = MerkleProof::prune(&cell, |_| false)
+            .unwrap()
+            .to_cell(())
+            .unwrap();
+
+        let dump = proof.to_string();
+        assert!(dump.starts_with("SPECIAL x{03"), "{dump}");
+        assert_eq!(dump.parse::<Cell>().unwrap(), proof);
+    }
+}
@@ -0,0 +1,127 @@
+use core::fmt::{self, Debug};
+
+/// [Level mask](https://docs.ton.org/develop/data-formats/exotic-cells#cell-level) of a [`Cell`](crate::Cell).
+///
+/// `i`-th bit is set if the cell has a pruned branch of level `i + 1`
+/// among its descendants, so it has a separate hash for that level.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LevelMask(u8);
+
+impl LevelMask {
+    /// Maximum level of a cell
+    pub const MAX_LEVEL: u8 = 3;
+
+    /// Level mask of cells without pruned branches
+    pub const ZERO: Self = Self(0);
+
+    /// Create level mask, higher bits are ignored
+    #[inline]
+    #[must_use]
+    pub const fn new(mask: u8) -> Self {
+        Self(mask & 0b111)
+    }
+
+    /// Level mask with the only bit set for given level
+    #[inline]
+    #[must_use]
+    pub const fn from_level(level: u8) -> Self {
+        match level {
+            0 => Self::ZERO,
+            l => Self::new(1 << (l - 1)),
+        }
+    }
+
+    /// Raw bits of the mask
+    #[inline]
+    #[must_use]
+    pub const fn mask(self) -> u8 {
+        self.0
+    }
+
+    /// Level of the cell, i.e. index of the highest bit set
+    #[inline]
+    #[must_use]
+    pub const fn level(self) -> u8 {
+        (u8::BITS - self.0.leading_zeros()) as u8
+    }
+
+    /// Index of the hash for this mask
+    #[inline]
+    #[must_use]
+    pub const fn hash_index(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Number of hashes the cell has
+    #[inline]
+    #[must_use]
+    pub const fn hashes_count(self) -> usize {
+        self.hash_index() + 1
+    }
+
+    /// Mask as if the cell was viewed from given level
+    #[inline]
+    #[must_use]
+    pub const fn apply(self, level: u8) -> Self {
+        if level >= Self::MAX_LEVEL {
+            return self;
+        }
+        Self(self.0 & ((1 << level) - 1))
+    }
+
+    /// Returns whether the cell has separate hash for given level
+    #[inline]
+    #[must_use]
+    pub const fn is_significant(self, level: u8) -> bool {
+        level == 0 || (self.0 >> (level - 1)) & 1 == 1
+    }
+
+    /// Mask of a cell which is `n` levels above, i.e. as if it was
+    /// a [Merkle proof](crate::MerkleProof) for this one
+    #[inline]
+    #[must_use]
+    pub const fn shift_right(self, n: u8) -> Self {
+        Self(self.0 >> n)
+    }
+}
+
+impl core::ops::BitOr for LevelMask {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl Debug for LevelMask {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:03b}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn level() {
+        assert_eq!(LevelMask::ZERO.level(), 0);
+        assert_eq!(LevelMask::new(0b001).level(), 1);
+        assert_eq!(LevelMask::new(0b010).level(), 2);
+        assert_eq!(LevelMask::new(0b101).level(), 3);
+        assert_eq!(LevelMask::from_level(3), LevelMask::new(0b100));
+    }
+
+    #[test]
+    fn hash_index() {
+        let mask = LevelMask::new(0b101);
+        assert_eq!(mask.hashes_count(), 3);
+        assert_eq!(mask.apply(0).hash_index(), 0);
+        assert_eq!(mask.apply(1).hash_index(), 1);
+        assert_eq!(mask.apply(2).hash_index(), 1);
+        assert_eq!(mask.apply(3).hash_index(), 2);
+        assert!(!mask.is_significant(2));
+        assert!(mask.is_significant(3));
+    }
+}
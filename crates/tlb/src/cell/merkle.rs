@@ -0,0 +1,369 @@
+use core::cell::RefCell;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use crate::{
+    Cell, CellType, Error, MerkleProof, MerkleUpdate, PrunedBranch, StringError,
+    ser::CellSerializeExt,
+};
+
+/// Recorder of cells loaded by [`CellParser`](crate::de::CellParser)
+/// created with [`Cell::parser_with_used()`].
+///
+/// Used to build [`MerkleProof`] of everything that was parsed.
+#[derive(Debug, Default)]
+pub struct UsedCells(RefCell<HashSet<[u8; 32]>>);
+
+impl UsedCells {
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mark given cell as used
+    #[inline]
+    pub fn insert(&self, cell: &Cell) {
+        self.0.borrow_mut().insert(cell.hash());
+    }
+
+    /// Returns whether given cell was used
+    #[inline]
+    pub fn contains(&self, cell: &Cell) -> bool {
+        self.0.borrow().contains(&cell.hash())
+    }
+
+    /// Number of used cells
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.borrow().len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Representation hashes of used cells
+    #[inline]
+    pub fn into_inner(self) -> HashSet<[u8; 32]> {
+        self.0.into_inner()
+    }
+}
+
+impl MerkleProof {
+    /// Create Merkle proof for given tree, where all subtrees starting
+    /// from cells not satisfying `keep` are replaced with [`PrunedBranch`]es.
+    ///
+    /// ```rust
+    /// # use tlb::{
+    /// #     bits::de::BitReaderExt,
+    /// #     ser::{CellSerializeExt, CellSerializeWrapAsExt},
+    /// #     Cell, Data, MerkleProof, Ref, StringError, UsedCells,
+    /// # };
+    /// # fn main() -> Result<(), StringError> {
+    /// let root = (1u8.wrap_as::<Ref<Data>>(), 2u8.wrap_as::<Ref<Data>>()).to_cell(((), ()))?;
+    ///
+    /// let used = UsedCells::new();
+    /// let mut parser = root.parser_with_used(&used);
+    /// let first: u8 = parser.parse_as::<_, Ref<Data>>(())?;
+    ///
+    /// let proof = MerkleProof::prune(&root, |c| used.contains(c))?;
+    /// assert_eq!(proof.virtual_hash, root.hash());
+    /// # assert_eq!(first, 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn prune(root: &Cell, mut keep: impl FnMut(&Cell) -> bool) -> Result<Self, StringError> {
+        let mut pruned = HashMap::new();
+        let root = prune_cell(root, 0, &mut keep, &mut pruned)?;
+        Ok(Self::new(root))
+    }
+
+    /// Shortcut for [`MerkleProof::prune`] which keeps only used cells
+    #[inline]
+    pub fn from_used(root: &Cell, used: &UsedCells) -> Result<Self, StringError> {
+        Self::prune(root, |c| used.contains(c))
+    }
+}
+
+impl MerkleProof {
+    /// Verify that this proof is for the tree with given representation
+    /// hash and return its virtual root.
+    ///
+    /// Virtual root can be parsed as usual, while reading data from
+    /// cells not included into the proof fails with
+    /// [pruned branch](crate::de::CellParser::ensure_not_pruned) error.
+    pub fn verify(&self, root_hash: [u8; 32]) -> Result<&Arc<Cell>, StringError> {
+        ensure_hash("virtual root", root_hash, self.virtual_hash)?;
+        ensure_hash(
+            "virtual root",
+            root_hash,
+            self.virtual_root.hash_at_level(0),
+        )?;
+        Ok(&self.virtual_root)
+    }
+
+    /// Shortcut for parsing [`MerkleProof`] from given cell and
+    /// [verifying](MerkleProof::verify) it.
+    #[inline]
+    pub fn verify_cell(proof: &Cell, root_hash: [u8; 32]) -> Result<Arc<Cell>, StringError> {
+        let proof: Self = proof.parse_fully(())?;
+        proof.verify(root_hash).cloned()
+    }
+}
+
+impl MerkleUpdate {
+    /// Verify that this update transforms tree with `old_hash` into
+    /// tree with `new_hash`
+    pub fn verify(&self, old_hash: [u8; 32], new_hash: [u8; 32]) -> Result<(), StringError> {
+        ensure_hash("old", old_hash, self.old_hash)?;
+        ensure_hash("old", old_hash, self.old.hash_at_level(0))?;
+        ensure_hash("new", new_hash, self.new_hash)?;
+        ensure_hash("new", new_hash, self.new.hash_at_level(0))?;
+        Ok(())
+    }
+
+    /// Apply this update to the full `old` tree and return the new one.
+    ///
+    /// Pruned branches of the new tree are resolved from subtrees of the
+    /// old tree, which are referenced from unpruned cells of
+    /// [`MerkleUpdate::old`].
+    pub fn apply(&self, old: &Arc<Cell>) -> Result<Arc<Cell>, StringError> {
+        self.verify(old.hash_at_level(0), self.new_hash)?;
+
+        let mut known = HashMap::new();
+        collect_known(&self.old, old, &mut known);
+
+        let mut resolved = HashMap::new();
+        let new = resolve_pruned(&self.new, 0, &known, &mut resolved)?;
+        ensure_hash("new", self.new_hash, new.hash())?;
+        Ok(new)
+    }
+}
+
+#[inline]
+fn ensure_hash(name: &str, expected: [u8; 32], got: [u8; 32]) -> Result<(), StringError> {
+    if got != expected {
+        return Err(Error::custom(format!(
+            "{name} hash mismatch: expected {}, got {}",
+            hex::encode(expected),
+            hex::encode(got),
+        )));
+    }
+    Ok(())
+}
+
+/// Walks pruned `update` tree together with the full `old` one and
+/// remembers all reachable subtrees of the latter by their hashes
+fn collect_known(update: &Cell, old: &Arc<Cell>, known: &mut HashMap<[u8; 32], Arc<Cell>>) {
+    if known.insert(old.hash(), old.clone()).is_some()
+        || update.cell_type() == CellType::PrunedBranch
+    {
+        return;
+    }
+    for (u, o) in update.references().iter().zip(old.references()) {
+        collect_known(u, o, known);
+    }
+}
+
+/// Replaces pruned branches of given Merkle depth with known subtrees
+fn resolve_pruned(
+    cell: &Arc<Cell>,
+    merkle_depth: u8,
+    known: &HashMap<[u8; 32], Arc<Cell>>,
+    resolved: &mut HashMap<([u8; 32], u8), Arc<Cell>>,
+) -> Result<Arc<Cell>, StringError> {
+    if cell.level() <= merkle_depth {
+        return Ok(cell.clone());
+    }
+    if let Some(r) = resolved.get(&(cell.hash(), merkle_depth)) {
+        return Ok(r.clone());
+    }
+
+    let r = if cell.cell_type() == CellType::PrunedBranch && cell.level() == merkle_depth + 1 {
+        let hash = cell.hash_at_level(merkle_depth);
+        known
+            .get(&hash)
+            .cloned()
+            .ok_or_else(|| Error::custom(format!("pruned branch: {}", hex::encode(hash))))?
+    } else {
+        let depth = merkle_depth + cell.cell_type().child_level_shift();
+        let references = cell
+            .references()
+            .iter()
+            .map(|r| resolve_pruned(r, depth, known, resolved))
+            .collect::<Result<_, _>>()?;
+        Cell::from_parts(cell.is_exotic(), cell.data().to_bitvec(), references)?.into()
+    };
+    resolved.insert((cell.hash(), merkle_depth), r.clone());
+    Ok(r)
+}
+
+/// Prunes the subtree, results are memoized by representation hash and
+/// Merkle depth, so shared subtrees are processed only once.
+fn prune_cell(
+    cell: &Cell,
+    merkle_depth: u8,
+    keep: &mut impl FnMut(&Cell) -> bool,
+    pruned: &mut HashMap<([u8; 32], u8), Arc<Cell>>,
+) -> Result<Arc<Cell>, StringError> {
+    if let Some(p) = pruned.get(&(cell.hash(), merkle_depth)) {
+        return Ok(p.clone());
+    }
+
+    let p: Arc<Cell> = if cell.cell_type() == CellType::PrunedBranch {
+        cell.clone().into()
+    } else if !keep(cell) {
+        PrunedBranch::new(cell, merkle_depth + 1)?
+            .to_cell(())?
+            .into()
+    } else {
+        let depth = merkle_depth + cell.cell_type().child_level_shift();
+        let references = cell
+            .references()
+            .iter()
+            .map(|r| prune_cell(r, depth, keep, pruned))
+            .collect::<Result<_, _>>()?;
+        Cell::from_parts(cell.is_exotic(), cell.data().to_bitvec(), references)?.into()
+    };
+    pruned.insert((cell.hash(), merkle_depth), p.clone());
+    Ok(p)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        r#as::{Data, Ref, Same},
+        bits::NoArgs,
+        ser::CellSerializeWrapAsExt,
+    };
+
+    use super::*;
+
+    /// ```text
+    /// root -> { a -> { c }, b }
+    /// ```
+    fn tree() -> Cell {
+        (
+            (0xA_u8.wrap_as::<Data>(), 0xC_u8.wrap_as::<Ref<Data>>()).wrap_as::<Ref>(),
+            0xB_u8.wrap_as::<Ref<Data>>(),
+        )
+            .to_cell(NoArgs::EMPTY)
+            .unwrap()
+    }
+
+    #[test]
+    fn prune_used() {
+        let root = tree();
+        let used = UsedCells::new();
+        let mut parser = root.parser_with_used(&used);
+        let (a, _): (u8, Cell) = parser
+            .parse_as::<_, Ref<(Data, Same)>>(NoArgs::EMPTY)
+            .unwrap();
+        assert_eq!(a, 0xA);
+        // `c` is taken as a whole, but not loaded
+        assert_eq!(used.len(), 2);
+
+        let proof = MerkleProof::from_used(&root, &used).unwrap();
+        assert_eq!(proof.virtual_hash, root.hash());
+        assert_eq!(proof.depth, root.max_depth());
+
+        let root = &proof.virtual_root;
+        assert_eq!(root.level(), 1);
+        let [a, b] = root.references() else {
+            unreachable!()
+        };
+        assert_eq!(a.cell_type(), CellType::Ordinary);
+        assert_eq!(a.references()[0].cell_type(), CellType::PrunedBranch);
+        assert_eq!(b.cell_type(), CellType::PrunedBranch);
+
+        let proof = proof.to_cell(()).unwrap();
+        assert_eq!(proof.level(), 0);
+    }
+
+    #[test]
+    fn prune_all() {
+        let root = tree();
+        let proof = MerkleProof::prune(&root, |_| false).unwrap();
+        assert_eq!(proof.virtual_root.cell_type(), CellType::PrunedBranch);
+        assert_eq!(proof.virtual_hash, root.hash());
+    }
+
+    #[test]
+    fn prune_shared_subtrees() {
+        let mut cell = Arc::new(Cell::new());
+        for _ in 0..100 {
+            cell = Cell::from_parts(false, [true].into_iter().collect(), vec![cell.clone(); 2])
+                .unwrap()
+                .into();
+        }
+        let proof = MerkleProof::prune(&cell, |c| c.max_depth() > 50).unwrap();
+        assert_eq!(proof.virtual_hash, cell.hash());
+        assert_eq!(proof.to_cell(()).unwrap().level(), 0);
+    }
+
+    #[test]
+    fn verify_proof() {
+        let root = tree();
+        let proof = MerkleProof::prune(&root, |c| c.max_depth() > 0).unwrap();
+        let proof = proof.to_cell(()).unwrap();
+
+        let virtual_root = MerkleProof::verify_cell(&proof, root.hash()).unwrap();
+        let mut parser = virtual_root.parser();
+        let (a, c): (u8, Cell) = parser
+            .parse_as::<_, Ref<(Data, Same)>>(NoArgs::EMPTY)
+            .unwrap();
+        assert_eq!(a, 0xA);
+        // rest of `a` with reference to pruned `c`
+        assert_eq!(c.references()[0].cell_type(), CellType::PrunedBranch);
+
+        let err = parser.parse_as::<u8, Ref<Data>>(NoArgs::EMPTY).unwrap_err();
+        assert!(err.to_string().contains("pruned branch"), "{err}");
+
+        let err = MerkleProof::verify_cell(&proof, [0; 32]).unwrap_err();
+        assert!(err.to_string().contains("hash mismatch"), "{err}");
+    }
+
+    #[test]
+    fn apply_update() {
+        let old: Arc<Cell> = tree().into();
+        let [a, _] = old.references() else {
+            unreachable!()
+        };
+        // root' -> { a, b' }
+        let new: Arc<Cell> = Cell::from_parts(
+            false,
+            old.data().to_bitvec(),
+            vec![
+                a.clone(),
+                0xBB_u8
+                    .wrap_as::<Data>()
+                    .to_cell(NoArgs::EMPTY)
+                    .unwrap()
+                    .into(),
+            ],
+        )
+        .unwrap()
+        .into();
+
+        let update = MerkleUpdate::new(
+            MerkleProof::prune(&old, |c| c.hash() == old.hash())
+                .unwrap()
+                .virtual_root,
+            MerkleProof::prune(&new, |c| c.hash() != a.hash())
+                .unwrap()
+                .virtual_root,
+        );
+        update.verify(old.hash(), new.hash()).unwrap();
+        assert!(update.verify(new.hash(), old.hash()).is_err());
+
+        let updated = update.apply(&old).unwrap();
+        assert_eq!(updated, new);
+
+        let err = update.apply(&new).unwrap_err();
+        assert!(err.to_string().contains("hash mismatch"), "{err}");
+    }
+}
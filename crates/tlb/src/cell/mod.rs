@@ -0,0 +1,822 @@
+use core::{
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+    ops::BitOr,
+};
+use std::sync::Arc;
+
+use bitvec::{order::Msb0, slice::BitSlice, vec::BitVec};
+use digest::{Digest, Output};
+use sha2::Sha256;
+
+use crate::{
+    Error, StringError,
+    de::{CellDeserialize, CellDeserializeAs, CellParser, CellParserError},
+    ser::CellBuilder,
+};
+
+mod arena;
+mod diff;
+mod exotic;
+mod fift;
+mod level;
+mod merkle;
+mod slice;
+
+pub use self::{arena::*, diff::*, exotic::*, level::*, merkle::*, slice::*};
+
+/// A [Cell](https://docs.ton.org/develop/data-formats/cell-boc#cell).
+///
+/// Its [type](Cell::cell_type), [level mask](Cell::level_mask),
+/// [hashes](Cell::hash_at_level) and [depths](Cell::depth_at_level) are
+/// calculated and validated once on creation, so [`Hash`] and [`Eq`] are
+/// cheap and only compare representation hashes.
+#[derive(Clone)]
+pub struct Cell {
+    r#type: CellType,
+    data: BitVec<u8, Msb0>,
+    references: Vec<Arc<Self>>,
+
+    level_mask: LevelMask,
+    hashes: [[u8; 32]; 4],
+    depths: [u16; 4],
+}
+
+impl Cell {
+    /// Maximum [depth](Cell::max_depth) of a cell allowed by TVM
+    pub const MAX_DEPTH: u16 = 1024;
+
+    /// Representation hash of empty ordinary cell
+    const EMPTY_HASH: [u8; 32] = [
+        0x96, 0xa2, 0x96, 0xd2, 0x24, 0xf2, 0x85, 0xc6, 0x7b, 0xee, 0x93, 0xc3, 0x0f, 0x8a, 0x30,
+        0x91, 0x57, 0xf0, 0xda, 0xa3, 0x5d, 0xc5, 0xb8, 0x7e, 0x41, 0x0b, 0x78, 0x63, 0x0a, 0x09,
+        0xcf, 0xc7,
+    ];
+
+    /// Create new [`CellBuilder`]
+    #[inline]
+    #[must_use]
+    pub const fn builder() -> CellBuilder {
+        CellBuilder::new()
+    }
+
+    /// Create new [`CellBuilder`], which interns all references stored
+    /// into it in given [`CellArena`]
+    #[inline]
+    #[must_use]
+    pub fn builder_in(arena: &CellArena) -> CellBuilder {
+        CellBuilder::new_in(arena.clone())
+    }
+
+    /// Create empty cell
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            r#type: CellType::Ordinary,
+            data: BitVec::EMPTY,
+            references: Vec::new(),
+            level_mask: LevelMask::ZERO,
+            hashes: [Self::EMPTY_HASH; 4],
+            depths: [0; 4],
+        }
+    }
+
+    /// Create cell from its parts and calculate its level mask, hashes
+    /// and depths.
+    ///
+    /// Data of exotic cells should start with [tag](CellType::tag) of
+    /// their type and follow its layout. Depth of the cell should not
+    /// exceed [`MAX_DEPTH`](Cell::MAX_DEPTH).
+    pub fn from_parts(
+        is_exotic: bool,
+        mut data: BitVec<u8, Msb0>,
+        references: Vec<Arc<Self>>,
+    ) -> Result<Self, StringError> {
+        // hashes are calculated over raw bytes, so data should start from
+        // the first bit with the rest of the last byte cleared
+        data.force_align();
+        data.set_uninitialized(false);
+
+        let mut cell = Self {
+            r#type: CellType::Ordinary,
+            data,
+            references,
+            level_mask: LevelMask::ZERO,
+            hashes: [[0; 32]; 4],
+            depths: [0; 4],
+        };
+        cell.r#type = CellType::of(is_exotic, &cell.data)?;
+        cell.level_mask = cell.calc_level_mask()?;
+        cell.depths = cell.calc_depths()?;
+        cell.hashes = cell.calc_hashes::<Sha256>(Self::hash_at_level);
+        Ok(cell)
+    }
+
+    /// Consume `self` and return `(is_exotic, data, references)`
+    #[inline]
+    #[must_use]
+    pub fn into_parts(self) -> (bool, BitVec<u8, Msb0>, Vec<Arc<Self>>) {
+        (self.is_exotic(), self.data, self.references)
+    }
+
+    /// Type of this cell
+    #[inline]
+    #[must_use]
+    pub const fn cell_type(&self) -> CellType {
+        self.r#type
+    }
+
+    /// Returns whether this cell is [exotic](https://docs.ton.org/develop/data-formats/exotic-cells)
+    #[inline]
+    #[must_use]
+    pub const fn is_exotic(&self) -> bool {
+        self.r#type.is_exotic()
+    }
+
+    /// Data bits of this cell
+    #[inline]
+    #[must_use]
+    pub fn data(&self) -> &BitSlice<u8, Msb0> {
+        &self.data
+    }
+
+    /// References of this cell
+    #[inline]
+    #[must_use]
+    pub fn references(&self) -> &[Arc<Self>] {
+        &self.references
+    }
+
+    /// Return [`CellParser`] for this cell
+    #[inline]
+    #[must_use]
+    pub fn parser(&self) -> CellParser<'_> {
+        CellParser::new(self.r#type, &self.data, &self.references)
+    }
+
+    /// Return [`CellParser`] for this cell, which records this cell and
+    /// all the cells loaded by the parser later into `used`.
+    ///
+    /// See [`MerkleProof::prune`].
+    #[inline]
+    #[must_use]
+    pub fn parser_with_used<'de>(&'de self, used: &'de UsedCells) -> CellParser<'de> {
+        used.insert(self);
+        self.parser().with_used(used)
+    }
+
+    /// Shortcut for [`.parser()`](Cell::parser)[`.parse()`](CellParser::parse)[`.ensure_empty()`](CellParser::ensure_empty).
+    #[inline]
+    pub fn parse_fully<'de, T>(&'de self, args: T::Args) -> Result<T, CellParserError<'de>>
+    where
+        T: CellDeserialize<'de>,
+    {
+        let mut parser = self.parser();
+        let v = parser.parse(args)?;
+        parser.ensure_empty()?;
+        Ok(v)
+    }
+
+    /// Shortcut for [`.parser()`](Cell::parser)[`.parse_as()`](CellParser::parse_as)[`.ensure_empty()`](CellParser::ensure_empty).
+    #[inline]
+    pub fn parse_fully_as<'de, T, As>(&'de self, args: As::Args) -> Result<T, CellParserError<'de>>
+    where
+        As: CellDeserializeAs<'de, T> + ?Sized,
+    {
+        let mut parser = self.parser();
+        let v = parser.parse_as::<T, As>(args)?;
+        parser.ensure_empty()?;
+        Ok(v)
+    }
+
+    /// Returns whether this cell has no data and zero references.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty() && self.references.is_empty()
+    }
+
+    #[inline]
+    fn data_bytes(&self) -> (usize, &[u8]) {
+        (self.data.len(), self.data.as_raw_slice())
+    }
+
+    /// See [Cell level](https://docs.ton.org/develop/data-formats/cell-boc#cell-level)
+    #[inline]
+    pub const fn level(&self) -> u8 {
+        self.level_mask.level()
+    }
+
+    /// See [Cell level](https://docs.ton.org/develop/data-formats/cell-boc#cell-level)
+    #[inline]
+    pub const fn level_mask(&self) -> LevelMask {
+        self.level_mask
+    }
+
+    /// Validates layout of the cell according to its type and
+    /// calculates its level mask
+    fn calc_level_mask(&self) -> Result<LevelMask, StringError> {
+        let refs_mask = || {
+            self.references
+                .iter()
+                .map(|r| r.level_mask)
+                .fold(LevelMask::ZERO, BitOr::bitor)
+        };
+        let ensure_layout = |bits: usize, refs: usize| {
+            if self.data.len() != bits || self.references.len() != refs {
+                return Err(Error::custom(format!(
+                    "{:?} must have {bits} bits and {refs} references, got: {} bits, {} references",
+                    self.r#type,
+                    self.data.len(),
+                    self.references.len(),
+                )));
+            }
+            Ok(())
+        };
+        let data = self.data.as_raw_slice();
+
+        Ok(match self.r#type {
+            CellType::Ordinary => refs_mask(),
+            CellType::PrunedBranch => {
+                let mask = LevelMask::new(*data.get(1).unwrap_or(&0));
+                if mask.level() == 0 || mask.mask() != data[1] {
+                    return Err(Error::custom(format!(
+                        "invalid pruned branch level mask: {:#010b}",
+                        data.get(1).unwrap_or(&0),
+                    )));
+                }
+                ensure_layout(8 + 8 + mask.hash_index() * (256 + 16), 0)?;
+                mask
+            }
+            CellType::LibraryReference => {
+                ensure_layout(8 + 256, 0)?;
+                LevelMask::ZERO
+            }
+            CellType::MerkleProof => {
+                ensure_layout(8 + 256 + 16, 1)?;
+                self.ensure_merkle_child(0, &data[1..33], &data[33..35])?;
+                refs_mask().shift_right(1)
+            }
+            CellType::MerkleUpdate => {
+                ensure_layout(8 + 2 * 256 + 2 * 16, 2)?;
+                self.ensure_merkle_child(0, &data[1..33], &data[65..67])?;
+                self.ensure_merkle_child(1, &data[33..65], &data[67..69])?;
+                refs_mask().shift_right(1)
+            }
+        })
+    }
+
+    /// Merkle cells should store level-0 hash and depth of their children
+    fn ensure_merkle_child(&self, i: usize, hash: &[u8], depth: &[u8]) -> Result<(), StringError> {
+        let child = &self.references[i];
+        if hash != child.hash_at_level(0) {
+            return Err(Error::custom(format!(
+                "{:?} hash mismatch in reference [{i}]: stored {}, actual {}",
+                self.r#type,
+                hex::encode(hash),
+                hex::encode(child.hash_at_level(0)),
+            )));
+        }
+        let depth = u16::from_be_bytes([depth[0], depth[1]]);
+        if depth != child.depth_at_level(0) {
+            return Err(Error::custom(format!(
+                "{:?} depth mismatch in reference [{i}]: stored {depth}, actual {}",
+                self.r#type,
+                child.depth_at_level(0),
+            )));
+        }
+        Ok(())
+    }
+
+    /// Hashes and depths stored in data of pruned branch
+    #[inline]
+    fn pruned_hash_and_depth(&self, i: usize) -> ([u8; 32], u16) {
+        let data = self.data.as_raw_slice();
+        let n = self.level_mask.hash_index();
+        let mut hash = [0; 32];
+        hash.copy_from_slice(&data[2 + 32 * i..2 + 32 * (i + 1)]);
+        let depth_offset = 2 + 32 * n + 2 * i;
+        (
+            hash,
+            u16::from_be_bytes([data[depth_offset], data[depth_offset + 1]]),
+        )
+    }
+
+    /// See [Cell serialization](https://docs.ton.org/develop/data-formats/cell-boc#cell-serialization)
+    #[inline]
+    fn refs_descriptor(&self, level_mask: LevelMask) -> u8 {
+        let is_exotic: u8 = if self.is_exotic() { 1 } else { 0 };
+        self.references.len() as u8 | (is_exotic << 3) | (level_mask.mask() << 5)
+    }
+
+    /// See [Cell serialization](https://docs.ton.org/develop/data-formats/cell-boc#cell-serialization)
+    #[inline]
+    fn bits_descriptor(&self) -> u8 {
+        let b = self.data.len();
+        (b / 8) as u8 + b.div_ceil(8) as u8
+    }
+
+    /// See [Cell depth](https://docs.ton.org/develop/data-formats/cell-boc#cell-depth)
+    #[inline]
+    pub const fn max_depth(&self) -> u16 {
+        self.depth_at_level(LevelMask::MAX_LEVEL)
+    }
+
+    /// Depth of the cell as seen from given level, i.e. with pruned
+    /// branches of lower levels being replaced with original subtrees.
+    #[inline]
+    pub const fn depth_at_level(&self, level: u8) -> u16 {
+        self.depths[self.level_mask.apply(level).hash_index()]
+    }
+
+    /// Calculates depths for all significant levels and ensures they
+    /// do not exceed [`MAX_DEPTH`](Cell::MAX_DEPTH)
+    fn calc_depths(&self) -> Result<[u16; 4], StringError> {
+        let shift = self.r#type.child_level_shift();
+        let mut depths = [0; 4];
+        let levels = (0..=self.level()).filter(|l| self.level_mask.is_significant(*l));
+        for (i, level) in levels.enumerate() {
+            depths[i] = if self.r#type == CellType::PrunedBranch && i < self.level_mask.hash_index()
+            {
+                self.pruned_hash_and_depth(i).1
+            } else {
+                self.references
+                    .iter()
+                    .map(|r| r.depth_at_level(level + shift))
+                    .max()
+                    .map_or(Some(0), |d| d.checked_add(1))
+                    .filter(|d| *d <= Self::MAX_DEPTH)
+                    .ok_or_else(|| {
+                        Error::custom(format!("depth exceeds maximum of {}", Self::MAX_DEPTH))
+                    })?
+            };
+        }
+        Ok(depths)
+    }
+
+    /// [Standard Cell representation hash](https://docs.ton.org/develop/data-formats/cell-boc#standard-cell-representation-hash-calculation)
+    /// with given digest.
+    ///
+    /// Unlike [`.hash()`](Cell::hash), it is not cached and is calculated
+    /// recursively for all references.
+    #[inline]
+    pub fn hash_digest<D>(&self) -> [u8; 32]
+    where
+        D: Digest,
+        Output<D>: Into<[u8; 32]>,
+    {
+        self.hash_digest_at_level::<D>(LevelMask::MAX_LEVEL)
+    }
+
+    fn hash_digest_at_level<D>(&self, level: u8) -> [u8; 32]
+    where
+        D: Digest,
+        Output<D>: Into<[u8; 32]>,
+    {
+        self.calc_hashes::<D>(|r, l| r.hash_digest_at_level::<D>(l))
+            [self.level_mask.apply(level).hash_index()]
+    }
+
+    /// Calculates hashes for all significant levels, see
+    /// [Higher hashes](https://docs.ton.org/develop/data-formats/exotic-cells#higher-hashes)
+    fn calc_hashes<D>(&self, ref_hash: impl Fn(&Self, u8) -> [u8; 32]) -> [[u8; 32]; 4]
+    where
+        D: Digest,
+        Output<D>: Into<[u8; 32]>,
+    {
+        let shift = self.r#type.child_level_shift();
+        // pruned branch stores its lower hashes in data
+        let offset = if self.r#type == CellType::PrunedBranch {
+            self.level_mask.hash_index()
+        } else {
+            0
+        };
+
+        let mut hashes = [[0; 32]; 4];
+        let levels = (0..=self.level()).filter(|l| self.level_mask.is_significant(*l));
+        for (i, level) in levels.enumerate() {
+            if i < offset {
+                hashes[i] = self.pruned_hash_and_depth(i).0;
+                continue;
+            }
+
+            let mut d = D::new();
+            d.update([
+                self.refs_descriptor(self.level_mask.apply(level)),
+                self.bits_descriptor(),
+            ]);
+
+            if i == offset {
+                let rest_bits = self.data.len() % 8;
+                if rest_bits == 0 {
+                    d.update(self.data.as_raw_slice());
+                } else {
+                    let (last, data) = self
+                        .data
+                        .as_raw_slice()
+                        .split_last()
+                        .unwrap_or_else(|| unreachable!());
+                    d.update(data);
+                    let mut last = last & (!0u8 << (8 - rest_bits)); // clear the rest
+                    last |= 1 << (8 - rest_bits - 1); // put stop-bit
+                    d.update([last])
+                }
+            } else {
+                d.update(hashes[i - 1]);
+            }
+
+            // refs depth
+            for r in &self.references {
+                d.update(r.depth_at_level(level + shift).to_be_bytes());
+            }
+
+            // refs hashes
+            for r in &self.references {
+                d.update(ref_hash(r, level + shift));
+            }
+
+            hashes[i] = d.finalize().into();
+        }
+        hashes
+    }
+
+    /// [Standard Cell representation hash](https://docs.ton.org/develop/data-formats/cell-boc#cell-hash),
+    /// i.e. hash of the highest level.
+    #[inline]
+    #[must_use]
+    pub const fn hash(&self) -> [u8; 32] {
+        self.hash_at_level(LevelMask::MAX_LEVEL)
+    }
+
+    /// [Higher hash](https://docs.ton.org/develop/data-formats/exotic-cells#higher-hashes)
+    /// of the cell as seen from given level, i.e. with pruned branches of
+    /// lower levels being replaced with original subtrees.
+    #[inline]
+    #[must_use]
+    pub const fn hash_at_level(&self, level: u8) -> [u8; 32] {
+        self.hashes[self.level_mask.apply(level).hash_index()]
+    }
+}
+
+impl Default for Cell {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PartialEq for Cell {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        Cell::hash(self) == Cell::hash(other)
+    }
+}
+
+impl Eq for Cell {}
+
+impl Hash for Cell {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Cell::hash(self).hash(state)
+    }
+}
+
+impl Debug for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "{}[0b", self.data.len())?;
+            for bit in &self.data {
+                write!(f, "{}", if *bit { '1' } else { '0' })?;
+            }
+            write!(f, "]")?;
+        } else {
+            let (bits_len, data) = self.data_bytes();
+            write!(f, "{}[0x{}]", bits_len, hex::encode_upper(data))?;
+        }
+        if self.references.is_empty() {
+            return Ok(());
+        }
+        write!(f, " -> ")?;
+        f.debug_set().entries(&self.references).finish()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+const _: () = {
+    use arbitrary::{Arbitrary, Error, MaxRecursionReached, Result, Unstructured, size_hint};
+    use bitvec::mem::bits_of;
+
+    use crate::ser::{MAX_BITS_LEN, MAX_REFS_COUNT};
+
+    impl<'a> Arbitrary<'a> for Cell {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            Self::from_parts(
+                false,
+                {
+                    let len_bytes = u
+                        .arbitrary_len::<u8>()?
+                        .min(MAX_BITS_LEN.div_ceil(bits_of::<u8>()));
+                    let bytes = u.bytes(len_bytes)?;
+                    let mut bits = BitVec::from_slice(bytes);
+                    bits.truncate(MAX_BITS_LEN);
+                    bits
+                },
+                u.arbitrary_iter()?
+                    .take(MAX_REFS_COUNT)
+                    .collect::<Result<_>>()?,
+            )
+            .map_err(|_| Error::IncorrectFormat)
+        }
+
+        #[inline]
+        fn size_hint(depth: usize) -> (usize, Option<usize>) {
+            Self::try_size_hint(depth).unwrap_or_default()
+        }
+
+        fn try_size_hint(depth: usize) -> Result<(usize, Option<usize>), MaxRecursionReached> {
+            size_hint::try_recursion_guard(depth, |depth| {
+                Ok(size_hint::and(
+                    (0, Some(MAX_BITS_LEN.div_ceil(bits_of::<u8>()))),
+                    <Vec<Arc<Self>> as Arbitrary>::size_hint(depth),
+                ))
+            })
+        }
+
+        fn arbitrary_take_rest(mut u: Unstructured<'a>) -> Result<Self> {
+            Self::from_parts(
+                false,
+                {
+                    let len_bytes = u.len().min(MAX_BITS_LEN.div_ceil(bits_of::<u8>()));
+                    let bytes = u.bytes(len_bytes)?;
+                    let mut bits = BitVec::from_slice(bytes);
+                    bits.truncate(MAX_BITS_LEN);
+                    bits
+                },
+                u.arbitrary_take_rest_iter()?
+                    .take(MAX_REFS_COUNT)
+                    .collect::<Result<_>>()?,
+            )
+            .map_err(|_| Error::IncorrectFormat)
+        }
+    }
+};
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+
+    use crate::{
+        BagOfCells, BagOfCellsArgs,
+        r#as::{Data, Ref},
+        bits::{NBits, NoArgs, de::BitReaderExt, ser::BitWriterExt},
+        ser::{CellSerializeExt, CellSerializeWrapAsExt},
+        tests::assert_store_parse_as_eq,
+    };
+
+    use super::*;
+
+    #[test]
+    fn zero_depth() {
+        assert_eq!(().to_cell(()).unwrap().max_depth(), 0)
+    }
+
+    #[test]
+    fn max_depth() {
+        let cell = (
+            ().wrap_as::<Ref>(),
+            (().wrap_as::<Ref>(), ().wrap_as::<Ref<Ref>>())
+                .wrap_as::<Ref>()
+                .wrap_as::<Ref>(),
+            ((), ()),
+        )
+            .to_cell(NoArgs::EMPTY)
+            .unwrap();
+        assert_eq!(cell.max_depth(), 4)
+    }
+
+    #[test]
+    fn cell_serde() {
+        assert_store_parse_as_eq::<
+            _,
+            (
+                Data<NBits<1>>,
+                Ref<Data<NBits<24>>>,
+                Ref<(Data<NBits<7>>, Ref<Data<NBits<24>>>)>,
+            ),
+        >((0b1, 0x0AAAAA, (0x7F, 0x0AAAAA)), NoArgs::EMPTY);
+    }
+
+    #[test]
+    fn hash_no_refs() {
+        let mut builder = Cell::builder();
+        builder.pack_as::<_, NBits<32>>(0x0000000F, ()).unwrap();
+        let cell = builder.into_cell().unwrap();
+
+        assert_eq!(
+            cell.hash(),
+            hex!("57b520dbcb9d135863fc33963cde9f6db2ded1430d88056810a2c9434a3860f9")
+        );
+    }
+
+    #[test]
+    fn hash_with_refs() {
+        let mut builder = Cell::builder();
+        builder
+            .store_as::<_, Data<NBits<24>>>(0x00000B, ())
+            .unwrap()
+            .store_reference_as::<_, Data>(0x0000000F_u32, ())
+            .unwrap()
+            .store_reference_as::<_, Data>(0x0000000F_u32, ())
+            .unwrap();
+        let cell = builder.into_cell().unwrap();
+
+        assert_eq!(
+            cell.hash(),
+            hex!("f345277cc6cfa747f001367e1e873dcfa8a936b8492431248b7a3eeafa8030e7")
+        );
+    }
+
+    #[test]
+    fn empty_hash() {
+        assert_eq!(
+            Cell::new().hash(),
+            Cell::from_parts(false, BitVec::new(), Vec::new())
+                .unwrap()
+                .hash()
+        );
+        assert_eq!(Cell::new().hash(), Cell::new().hash_digest::<Sha256>());
+    }
+
+    #[test]
+    fn hash_unaligned() {
+        let mut builder = Cell::builder();
+        builder.pack_as::<_, NBits<12>>(0xABC, ()).unwrap();
+        let cell = builder.into_cell().unwrap();
+
+        let mut parser = cell.parser();
+        parser.unpack_as::<u8, NBits<4>>(()).unwrap();
+        let rest: Cell = parser.parse(()).unwrap();
+
+        assert_eq!(rest, 0xBC_u8.wrap_as::<Data>().to_cell(()).unwrap());
+        assert_eq!(rest.hash(), rest.hash_digest::<Sha256>());
+    }
+
+    #[test]
+    fn too_deep() {
+        let mut cell = Arc::new(Cell::new());
+        for _ in 0..Cell::MAX_DEPTH {
+            cell = Cell::from_parts(false, BitVec::new(), vec![cell])
+                .unwrap()
+                .into();
+        }
+        assert_eq!(cell.max_depth(), Cell::MAX_DEPTH);
+
+        let mut builder = Cell::builder();
+        builder.store_reference_cell(cell).unwrap();
+        assert!(builder.into_cell().is_err());
+    }
+
+    #[test]
+    fn hash_shared_subtrees() {
+        let mut cell = Arc::new(Cell::new());
+        for _ in 0..1000 {
+            cell = Cell::from_parts(false, BitVec::new(), vec![cell.clone(); 4])
+                .unwrap()
+                .into();
+        }
+        assert_eq!(cell.max_depth(), 1000);
+
+        let boc = BagOfCells::from_root(cell.clone())
+            .serialize(BagOfCellsArgs::default())
+            .unwrap();
+        let got = BagOfCells::deserialize(boc)
+            .unwrap()
+            .into_single_root()
+            .unwrap();
+        assert_eq!(Cell::hash(&got), Cell::hash(&cell));
+    }
+
+    #[test]
+    fn cell_exotic_serde() {
+        let child = 0x02_u8.wrap_as::<Data>().to_cell(()).unwrap();
+        let expected = MerkleProof::new(child).to_cell(()).unwrap();
+        assert_eq!(expected.cell_type(), CellType::MerkleProof);
+
+        let actual = expected
+            .to_cell(NoArgs::EMPTY)
+            .unwrap()
+            .parse_fully::<Cell>(NoArgs::EMPTY)
+            .unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    /// ```text
+    /// root -> { a -> { c }, b }
+    /// ```
+    fn tree() -> (Cell, Cell) {
+        let c = 0xC_u8.wrap_as::<Data>().to_cell(()).unwrap();
+        let a = (0xA_u8.wrap_as::<Data>(), c.wrap_as::<Ref>())
+            .to_cell(NoArgs::EMPTY)
+            .unwrap();
+        let b = 0xB_u8.wrap_as::<Data>().to_cell(()).unwrap();
+        let root = (a.clone().wrap_as::<Ref>(), b.wrap_as::<Ref>())
+            .to_cell(NoArgs::EMPTY)
+            .unwrap();
+        (root, a)
+    }
+
+    #[test]
+    fn pruned_branch() {
+        let (root, a) = tree();
+        let pruned = PrunedBranch::new(&a, 1).unwrap().to_cell(()).unwrap();
+        assert_eq!(pruned.cell_type(), CellType::PrunedBranch);
+        assert_eq!(pruned.level_mask(), LevelMask::new(0b001));
+        assert_eq!(pruned.hash_at_level(0), a.hash());
+        assert_eq!(pruned.depth_at_level(0), a.max_depth());
+        assert_ne!(pruned.hash(), a.hash());
+
+        let (_, mut data, mut references) = root.clone().into_parts();
+        references[0] = pruned.into();
+        let pruned_root = Cell::from_parts(false, data.clone(), references.clone()).unwrap();
+        assert_eq!(pruned_root.level(), 1);
+        assert_eq!(pruned_root.hash_at_level(0), root.hash());
+        assert_eq!(pruned_root.depth_at_level(0), root.max_depth());
+        assert_eq!(pruned_root.hash(), pruned_root.hash_digest::<Sha256>());
+
+        data.push(true);
+        references.truncate(1);
+        assert_ne!(
+            Cell::from_parts(false, data, references)
+                .unwrap()
+                .hash_at_level(0),
+            root.hash()
+        );
+    }
+
+    #[test]
+    fn merkle_proof() {
+        let (root, a) = tree();
+        let (_, data, mut references) = root.clone().into_parts();
+        references[0] = PrunedBranch::new(&a, 1)
+            .unwrap()
+            .to_cell(())
+            .unwrap()
+            .into();
+        let pruned_root = Cell::from_parts(false, data, references).unwrap();
+
+        let proof = MerkleProof::new(pruned_root).to_cell(()).unwrap();
+        assert_eq!(proof.level(), 0);
+
+        let parsed: MerkleProof = proof.parse_fully(()).unwrap();
+        assert_eq!(parsed.virtual_hash, root.hash());
+        assert_eq!(parsed.depth, root.max_depth());
+    }
+
+    #[test]
+    fn merkle_proof_hash_mismatch() {
+        let (root, _) = tree();
+        let mut proof = MerkleProof::new(root);
+        proof.virtual_hash[0] ^= 1;
+        let err = proof.to_cell(()).unwrap_err();
+        assert!(err.to_string().contains("hash mismatch"), "{err}");
+    }
+
+    #[test]
+    fn merkle_update() {
+        let (old, _) = tree();
+        let new = Cell::default();
+        let update = MerkleUpdate::new(old.clone(), new.clone())
+            .to_cell(())
+            .unwrap();
+        assert_eq!(update.cell_type(), CellType::MerkleUpdate);
+
+        let parsed: MerkleUpdate = update.parse_fully(()).unwrap();
+        assert_eq!(parsed.old_hash, old.hash());
+        assert_eq!(parsed.new_hash, new.hash());
+    }
+
+    #[test]
+    fn library_reference() {
+        let library = LibraryReference { hash: [1; 32] };
+        let cell = library.to_cell(()).unwrap();
+        assert_eq!(cell.cell_type(), CellType::LibraryReference);
+        assert_eq!(cell.parse_fully::<LibraryReference>(()).unwrap(), library);
+        assert!(cell.parse_fully::<PrunedBranch>(()).is_err());
+    }
+
+    #[test]
+    fn invalid_exotic_layout() {
+        let mut builder = Cell::builder();
+        builder.exotic().unwrap().pack(2u8, ()).unwrap();
+        assert!(builder.into_cell().is_err());
+
+        let mut builder = Cell::builder();
+        builder.exotic().unwrap().pack(5u8, ()).unwrap();
+        assert!(builder.into_cell().is_err());
+
+        let mut builder = Cell::builder();
+        builder.exotic().unwrap();
+        assert!(builder.into_cell().is_err());
+    }
+}
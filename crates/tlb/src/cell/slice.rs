@@ -0,0 +1,391 @@
+use core::{
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+    mem,
+    ops::Range,
+};
+use std::sync::Arc;
+
+use crate::{
+    Context, Error, StringError,
+    bits::{
+        bitvec::{order::Msb0, slice::BitSlice},
+        ser::BitWriter,
+    },
+    de::{CellDeserialize, CellDeserializeAs, CellParser, CellParserError},
+    ser::{CellBuilder, CellBuilderError, CellSerialize},
+};
+
+use super::{Cell, CellType};
+
+/// Owned part of a [`Cell`], similar to TVM `Slice`: a shared cell with
+/// ranges of its data bits and references, which are still left.
+///
+/// Unlike [`CellParser`], it does not borrow from the cell, so it can be
+/// stored, cloned cheaply and carried around, while parsing from it with
+/// [`.parse()`](CellSlice::parse) advances it forward. Storing it into
+/// [`CellBuilder`] copies remaining data bits and shares remaining
+/// references, same as TVM `STSLICE`.
+/// ```rust
+/// # use tlb::{
+/// #     ser::{CellSerializeExt, CellSerializeWrapAsExt},
+/// #     CellSlice, Data, Ref, StringError,
+/// # };
+/// # fn main() -> Result<(), StringError> {
+/// let cell = (1u8.wrap_as::<Data>(), 2u16.wrap_as::<Data>(), 3u8.wrap_as::<Ref<Data>>())
+///     .to_cell(((), (), ()))?;
+///
+/// let mut slice = CellSlice::new(cell);
+/// assert_eq!(slice.parse_as::<u8, Data>(())?, 1);
+/// assert_eq!((slice.bits_left(), slice.refs_left()), (16, 1));
+///
+/// let rest = (2u16.wrap_as::<Data>(), 3u8.wrap_as::<Ref<Data>>()).to_cell(((), ()))?;
+/// assert_eq!(slice.to_cell(())?, rest);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct CellSlice {
+    cell: Arc<Cell>,
+    bits: Range<usize>,
+    references: Range<usize>,
+}
+
+impl CellSlice {
+    /// Create slice covering the whole cell
+    #[inline]
+    #[must_use]
+    pub fn new(cell: impl Into<Arc<Cell>>) -> Self {
+        let cell = cell.into();
+        Self {
+            bits: 0..cell.data.len(),
+            references: 0..cell.references.len(),
+            cell,
+        }
+    }
+
+    /// Create slice covering given ranges of data bits and references
+    /// of the cell
+    pub fn with_ranges(
+        cell: impl Into<Arc<Cell>>,
+        bits: Range<usize>,
+        references: Range<usize>,
+    ) -> Result<Self, StringError> {
+        let cell = cell.into();
+        if bits.start > bits.end || bits.end > cell.data.len() {
+            return Err(Error::custom(format!(
+                "bits out of range: {bits:?}, cell has {}",
+                cell.data.len()
+            )));
+        }
+        if references.start > references.end || references.end > cell.references.len() {
+            return Err(Error::custom(format!(
+                "references out of range: {references:?}, cell has {}",
+                cell.references.len()
+            )));
+        }
+        Ok(Self {
+            cell,
+            bits,
+            references,
+        })
+    }
+
+    /// Underlying cell
+    #[inline]
+    pub fn cell(&self) -> &Arc<Cell> {
+        &self.cell
+    }
+
+    /// Range of data bits of [underlying cell](CellSlice::cell) left
+    #[inline]
+    pub fn bits_range(&self) -> Range<usize> {
+        self.bits.clone()
+    }
+
+    /// Range of references of [underlying cell](CellSlice::cell) left
+    #[inline]
+    pub fn references_range(&self) -> Range<usize> {
+        self.references.clone()
+    }
+
+    /// Data bits left
+    #[inline]
+    pub fn data(&self) -> &BitSlice<u8, Msb0> {
+        &self.cell.data[self.bits.clone()]
+    }
+
+    /// References left
+    #[inline]
+    pub fn references(&self) -> &[Arc<Cell>] {
+        &self.cell.references[self.references.clone()]
+    }
+
+    #[inline]
+    pub fn bits_left(&self) -> usize {
+        self.bits.len()
+    }
+
+    #[inline]
+    pub fn refs_left(&self) -> usize {
+        self.references.len()
+    }
+
+    /// Returns whether this slice has no more data and references.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty() && self.references.is_empty()
+    }
+
+    /// Returns type of the underlying cell
+    #[inline]
+    pub fn cell_type(&self) -> CellType {
+        self.cell.cell_type()
+    }
+
+    /// Return [`CellParser`] for the rest of this slice, which does not
+    /// advance it
+    #[inline]
+    #[must_use]
+    pub fn parser(&self) -> CellParser<'_> {
+        CellParser::new(self.cell.r#type, self.data(), self.references()).with_origin(
+            &self.cell,
+            self.bits.end,
+            self.references.end,
+        )
+    }
+
+    /// Parse the value with args using its [`CellDeserialize`]
+    /// implementation and advance this slice past it.
+    ///
+    /// The slice is left untouched on error.
+    #[inline]
+    pub fn parse<'a, T>(&'a mut self, args: T::Args) -> Result<T, StringError>
+    where
+        T: CellDeserialize<'a>,
+    {
+        self.parse_with(|parser| parser.parse(args))
+    }
+
+    /// Same as [`.parse()`](CellSlice::parse), but using an adapter
+    #[inline]
+    pub fn parse_as<'a, T, As>(&'a mut self, args: As::Args) -> Result<T, StringError>
+    where
+        As: CellDeserializeAs<'a, T> + ?Sized,
+    {
+        self.parse_with(|parser| parser.parse_as::<T, As>(args))
+    }
+
+    fn parse_with<'a, T>(
+        &'a mut self,
+        f: impl FnOnce(&mut CellParser<'a>) -> Result<T, StringError>,
+    ) -> Result<T, StringError> {
+        let cell: &'a Arc<Cell> = &self.cell;
+        let mut parser = CellParser::new(
+            cell.r#type,
+            &cell.data[self.bits.clone()],
+            &cell.references[self.references.clone()],
+        )
+        .with_origin(cell, self.bits.end, self.references.end);
+        let v = f(&mut parser)?;
+        self.bits.start = self.bits.end - parser.bits_left();
+        self.references.start = self.references.end - parser.references_left();
+        Ok(v)
+    }
+
+    /// Skip given number of data bits and references
+    pub fn advance(&mut self, bits: usize, references: usize) -> Result<(), StringError> {
+        if bits > self.bits_left() || references > self.refs_left() {
+            return Err(Error::custom(format!(
+                "cannot advance by {bits} bits, {references} references: only {} bits, {} references left",
+                self.bits_left(),
+                self.refs_left(),
+            )));
+        }
+        self.bits.start += bits;
+        self.references.start += references;
+        Ok(())
+    }
+}
+
+impl From<Arc<Cell>> for CellSlice {
+    #[inline]
+    fn from(cell: Arc<Cell>) -> Self {
+        Self::new(cell)
+    }
+}
+
+impl From<Cell> for CellSlice {
+    #[inline]
+    fn from(cell: Cell) -> Self {
+        Self::new(cell)
+    }
+}
+
+/// Slices are equal when their remaining data bits and references are
+/// equal, regardless of underlying cells and positions in them
+impl PartialEq for CellSlice {
+    fn eq(&self, other: &Self) -> bool {
+        self.cell.is_exotic() == other.cell.is_exotic()
+            && self.data() == other.data()
+            && self.references() == other.references()
+    }
+}
+
+impl Eq for CellSlice {}
+
+impl Hash for CellSlice {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.cell.is_exotic().hash(state);
+        self.data().hash(state);
+        self.references().hash(state);
+    }
+}
+
+impl Debug for CellSlice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CellSlice")
+            .field("bits", &self.bits)
+            .field("references", &self.references)
+            .field("cell", &self.cell)
+            .finish()
+    }
+}
+
+impl CellSerialize for CellSlice {
+    type Args = ();
+
+    #[inline]
+    fn store(&self, builder: &mut CellBuilder, _: Self::Args) -> Result<(), CellBuilderError> {
+        if self.cell.is_exotic() {
+            builder.exotic()?;
+        }
+        builder.write_bitslice(self.data())?;
+        for (i, r) in self.references().iter().enumerate() {
+            builder
+                .store_reference_cell(r.clone())
+                .with_context(|| format!("[{i}]"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Takes the rest of the parser. When it reads a loaded cell, e.g. a
+/// reference of [`Cell`] or another [`CellSlice`], the slice shares that
+/// cell without copying. Otherwise, e.g. for the root of
+/// [`Cell::parser()`] or cells of [`BocView`](crate::BocView), the rest is
+/// loaded into a new cell.
+impl<'de> CellDeserialize<'de> for CellSlice {
+    type Args = ();
+
+    fn parse(parser: &mut CellParser<'de>, args: Self::Args) -> Result<Self, CellParserError<'de>> {
+        let Some((cell, bits, references)) = parser.origin() else {
+            return parser.parse::<Cell>(args).map(Self::new);
+        };
+        // same as for Cell, data of pruned branches is taken as well
+        mem::take(parser.exotic_data());
+        parser.take_references();
+        Ok(Self {
+            cell: cell.clone(),
+            bits,
+            references,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        r#as::{Data, Ref},
+        ser::{CellSerializeExt, CellSerializeWrapAsExt},
+    };
+
+    use super::*;
+
+    fn cell() -> Arc<Cell> {
+        (
+            0xAB_u8.wrap_as::<Data>(),
+            0xCD_u8.wrap_as::<Data>(),
+            1u8.wrap_as::<Ref<Data>>(),
+            2u8.wrap_as::<Ref<Data>>(),
+        )
+            .to_cell(((), (), (), ()))
+            .map(Arc::new)
+            .unwrap()
+    }
+
+    #[test]
+    fn parse() {
+        let mut slice = CellSlice::new(cell());
+        assert_eq!(slice.parse_as::<u8, Data>(()).unwrap(), 0xAB);
+        assert_eq!(slice.parse_as::<u8, Ref<Data>>(()).unwrap(), 1);
+        assert_eq!(slice.bits_range(), 8..16);
+        assert_eq!(slice.references_range(), 1..2);
+
+        // failed parsing does not advance
+        assert!(slice.parse_as::<u16, Data>(()).is_err());
+        assert_eq!(slice.bits_left(), 8);
+
+        slice.advance(8, 1).unwrap();
+        assert!(slice.is_empty());
+        assert!(slice.advance(1, 0).is_err());
+    }
+
+    #[test]
+    fn store() {
+        let cell = cell();
+        let mut slice = CellSlice::new(cell.clone());
+        slice.advance(8, 1).unwrap();
+
+        let stored = slice.to_cell(()).unwrap();
+        assert_eq!(
+            stored,
+            (0xCD_u8.wrap_as::<Data>(), 2u8.wrap_as::<Ref<Data>>())
+                .to_cell(((), ()))
+                .unwrap()
+        );
+        // references are shared rather than copied
+        assert!(Arc::ptr_eq(&stored.references()[0], &cell.references()[1]));
+    }
+
+    #[test]
+    fn eq() {
+        let a = CellSlice::with_ranges(cell(), 8..16, 1..2).unwrap();
+        let b = CellSlice::new(
+            (0xCD_u8.wrap_as::<Data>(), 2u8.wrap_as::<Ref<Data>>())
+                .to_cell(((), ()))
+                .unwrap(),
+        );
+        assert_eq!(a, b);
+        assert_ne!(a, CellSlice::new(cell()));
+        assert!(CellSlice::with_ranges(cell(), 8..17, 0..0).is_err());
+    }
+
+    #[test]
+    fn parse_remainder() {
+        let cell = cell();
+        let mut parser = cell.parser();
+        parser.parse_as::<u8, Data>(()).unwrap();
+        let rest: CellSlice = parser.parse(()).unwrap();
+        assert_eq!(rest, CellSlice::with_ranges(cell, 8..16, 0..2).unwrap());
+    }
+
+    #[test]
+    fn parse_shared() {
+        let inner = cell();
+        let mut builder = Cell::builder();
+        builder.store_reference_cell(inner.clone()).unwrap();
+        let outer = builder.into_cell().unwrap();
+
+        let rest: CellSlice = outer.parse_fully_as::<_, Ref>(()).unwrap();
+        assert!(Arc::ptr_eq(rest.cell(), &inner));
+        assert_eq!((rest.bits_range(), rest.references_range()), (0..16, 0..2));
+
+        let mut slice = CellSlice::new(inner.clone());
+        slice.parse_as::<u8, Data>(()).unwrap();
+        let rest: CellSlice = slice.parse(()).unwrap();
+        assert!(Arc::ptr_eq(rest.cell(), &inner));
+        assert_eq!((rest.bits_range(), rest.references_range()), (8..16, 0..2));
+        assert!(slice.is_empty());
+    }
+}
@@ -4,6 +4,10 @@ mod parser;
 
 pub use self::{r#as::*, parser::*};
 
+/// Derive [`CellDeserialize`] implementation, requires `derive` feature
+#[cfg(feature = "derive")]
+pub use tlbits_derive::CellDeserialize;
+
 use std::{borrow::Cow, mem, rc::Rc, sync::Arc};
 
 use crate::{Cell, Context, FromInto, Same, bits::de::BitReaderExt, either::Either};
@@ -172,10 +176,10 @@ impl<'de> CellDeserialize<'de> for Cell {
 
     #[inline]
     fn parse(parser: &mut CellParser<'de>, _: Self::Args) -> Result<Self, CellParserError<'de>> {
-        Ok(Self {
-            is_exotic: parser.is_exotic,
-            data: mem::take(&mut parser.data).to_bitvec(),
-            references: mem::take(&mut parser.references).to_vec(),
-        })
+        Self::from_parts(
+            parser.is_exotic(),
+            mem::take(&mut parser.data).to_bitvec(),
+            parser.take_references().load()?,
+        )
     }
 }
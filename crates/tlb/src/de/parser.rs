@@ -1,10 +1,10 @@
-use core::{iter, mem};
+use core::{iter, mem, ops::Range};
 use std::{borrow::Cow, sync::Arc};
 
 use tlbits::Context;
 
 use crate::{
-    Cell, Error,
+    Cell, CellType, CellView, Error, StringError, UsedCells,
     bits::{
         bitvec::{order::Msb0, slice::BitSlice},
         de::BitReader,
@@ -17,27 +17,194 @@ use super::{CellDeserialize, CellDeserializeAs};
 pub type CellParserError<'de> = <CellParser<'de> as BitReader<'de>>::Error;
 
 /// Cell parser created with [`Cell::parser()`].
+///
+/// It can also parse cells, which are not loaded into memory, where
+/// references are resolved only when parsed, see [`CellView::parser()`]
+/// and [`LazyCell::parser()`](crate::LazyCell::parser).
 #[derive(Clone)]
 pub struct CellParser<'de> {
-    pub(super) is_exotic: bool,
+    pub(super) r#type: CellType,
     pub(super) data: &'de BitSlice<u8, Msb0>,
-    pub(super) references: &'de [Arc<Cell>],
+    pub(super) references: References<'de>,
+    pub(super) used: Option<&'de UsedCells>,
+    /// Number of references followed from the root parser
+    depth: u16,
+    origin: Option<Origin<'de>>,
+}
+
+/// Shared cell being parsed, where data bits and references left end at
+/// given positions, see [`CellParser::origin()`]
+#[derive(Clone, Copy)]
+struct Origin<'de> {
+    cell: &'de Arc<Cell>,
+    bits_end: usize,
+    references_end: usize,
+}
+
+/// References left in [`CellParser`]
+#[derive(Clone, Copy)]
+pub(crate) enum References<'de> {
+    /// Loaded cells
+    Cells(&'de [Arc<Cell>]),
+    /// References `next..end` of [`CellView`]
+    View {
+        cell: CellView<'de, 'de>,
+        next: usize,
+        end: usize,
+    },
+    /// References `next..end` of [`LazyReferences`]
+    Lazy {
+        cell: &'de dyn LazyReferences,
+        next: usize,
+        end: usize,
+    },
+}
+
+/// Cell which is not loaded into memory, so that its references are
+/// resolved only when parsed, see [`CellParser::lazy()`]
+pub(crate) trait LazyReferences {
+    /// Total number of references
+    fn references_len(&self) -> usize;
+
+    /// Return parser for `i`-th reference
+    fn reference_parser(&self, i: usize) -> Result<CellParser<'_>, StringError>;
+
+    /// Load `i`-th reference with its whole subtree
+    fn load_reference(&self, i: usize) -> Result<Arc<Cell>, StringError>;
+}
+
+impl<'de> References<'de> {
+    #[inline]
+    const fn len(&self) -> usize {
+        match self {
+            Self::Cells(references) => references.len(),
+            Self::View { next, end, .. } | Self::Lazy { next, end, .. } => *end - *next,
+        }
+    }
+
+    /// Take all references left, so that none of them are left in `self`
+    fn take(&mut self) -> Self {
+        let taken = *self;
+        match self {
+            Self::Cells(references) => *references = &[],
+            Self::View { next, end, .. } | Self::Lazy { next, end, .. } => *next = *end,
+        }
+        taken
+    }
+
+    /// Load all references left with their whole subtrees
+    pub(crate) fn load(self) -> Result<Vec<Arc<Cell>>, StringError> {
+        match self {
+            Self::Cells(references) => Ok(references.to_vec()),
+            Self::View { cell, next, end } => (next..end)
+                .map(|i| cell.reference(i)?.load().cloned())
+                .collect(),
+            Self::Lazy { cell, next, end } => (next..end).map(|i| cell.load_reference(i)).collect(),
+        }
+    }
 }
 
 impl<'de> CellParser<'de> {
     #[inline]
     pub(crate) const fn new(
-        is_exotic: bool,
+        r#type: CellType,
         data: &'de BitSlice<u8, Msb0>,
         references: &'de [Arc<Cell>],
+    ) -> Self {
+        Self::with_references(r#type, data, References::Cells(references))
+    }
+
+    /// Parser for cell of [`BocView`], see [`CellView::parser()`]
+    #[inline]
+    pub(crate) fn view(cell: CellView<'de, 'de>) -> Result<Self, StringError> {
+        Ok(Self::with_references(
+            CellType::of(cell.is_exotic(), cell.data())?,
+            cell.data(),
+            References::View {
+                cell,
+                next: 0,
+                end: cell.references_len(),
+            },
+        ))
+    }
+
+    /// Parser for a cell with given data, which references are resolved
+    /// on demand, e.g. [`LazyCell::parser()`](crate::LazyCell::parser)
+    #[inline]
+    pub(crate) fn lazy(
+        is_exotic: bool,
+        data: &'de BitSlice<u8, Msb0>,
+        cell: &'de dyn LazyReferences,
+    ) -> Result<Self, StringError> {
+        Ok(Self::with_references(
+            CellType::of(is_exotic, data)?,
+            data,
+            References::Lazy {
+                cell,
+                next: 0,
+                end: cell.references_len(),
+            },
+        ))
+    }
+
+    #[inline]
+    const fn with_references(
+        r#type: CellType,
+        data: &'de BitSlice<u8, Msb0>,
+        references: References<'de>,
     ) -> Self {
         Self {
-            is_exotic,
+            r#type,
             data,
             references,
+            used: None,
+            depth: 0,
+            origin: None,
         }
     }
 
+    /// Remember that this parser reads `cell` up to given positions, so
+    /// that the rest can be taken without copying, see [`CellParser::origin()`]
+    #[inline]
+    #[must_use]
+    pub(crate) const fn with_origin(
+        mut self,
+        cell: &'de Arc<Cell>,
+        bits_end: usize,
+        references_end: usize,
+    ) -> Self {
+        self.origin = Some(Origin {
+            cell,
+            bits_end,
+            references_end,
+        });
+        self
+    }
+
+    /// Shared cell being parsed along with ranges of its data bits and
+    /// references left, if this parser reads a loaded cell
+    pub(crate) fn origin(&self) -> Option<(&'de Arc<Cell>, Range<usize>, Range<usize>)> {
+        let Origin {
+            cell,
+            bits_end,
+            references_end,
+        } = self.origin?;
+        Some((
+            cell,
+            bits_end - self.bits_left()..bits_end,
+            references_end - self.references_left()..references_end,
+        ))
+    }
+
+    /// Record all cells loaded by this parser and its children into
+    /// `used`, see [`MerkleProof::prune`](crate::MerkleProof::prune).
+    #[inline]
+    #[must_use]
+    pub(crate) const fn with_used(mut self, used: &'de UsedCells) -> Self {
+        self.used = Some(used);
+        self
+    }
+
     /// Parse the value with args using its [`CellDeserialize`]
     /// implementation.
     #[inline]
@@ -96,14 +263,40 @@ impl<'de> CellParser<'de> {
             .map(|(i, v)| v.with_context(|| format!("[{i}]")))
     }
 
-    #[inline]
-    fn pop_reference(&mut self) -> Result<&'de Arc<Cell>, CellParserError<'de>> {
-        let (first, rest) = self
-            .references
-            .split_first()
-            .ok_or_else(|| Error::custom("no more references left"))?;
-        self.references = rest;
-        Ok(first)
+    /// Return parser for the next reference
+    fn pop_reference(&mut self) -> Result<CellParser<'de>, CellParserError<'de>> {
+        if self.references.len() == 0 {
+            return Err(Error::custom("no more references left"));
+        }
+        if self.depth >= Cell::MAX_DEPTH {
+            return Err(Error::custom(format!(
+                "depth exceeds maximum of {}",
+                Cell::MAX_DEPTH
+            )));
+        }
+        let mut parser = match &mut self.references {
+            References::Cells([first, rest @ ..]) => {
+                self.references = References::Cells(rest);
+                match self.used {
+                    Some(used) => first.parser_with_used(used),
+                    None => first.parser(),
+                }
+                .with_origin(first, first.data().len(), first.references().len())
+            }
+            References::Cells([]) => unreachable!(),
+            References::View { cell, next, .. } => {
+                let parser = cell.reference(*next)?.parser()?;
+                *next += 1;
+                parser
+            }
+            References::Lazy { cell, next, .. } => {
+                let parser = cell.reference_parser(*next)?;
+                *next += 1;
+                parser
+            }
+        };
+        parser.depth = self.depth + 1;
+        Ok(parser)
     }
 
     #[inline]
@@ -114,7 +307,16 @@ impl<'de> CellParser<'de> {
     where
         As: CellDeserializeAs<'de, T> + ?Sized,
     {
-        self.pop_reference()?.parse_fully_as::<T, As>(args)
+        let mut parser = self.pop_reference()?;
+        let v = parser.parse_as::<T, As>(args)?;
+        parser.ensure_empty()?;
+        Ok(v)
+    }
+
+    /// Take all references left
+    #[inline]
+    pub(crate) fn take_references(&mut self) -> References<'de> {
+        self.references.take()
     }
 
     #[inline]
@@ -150,16 +352,22 @@ impl<'de> CellParser<'de> {
             return Err(Error::custom(format!(
                 "more data left: {} bits, {} references",
                 self.data.len(),
-                self.references.len(),
+                self.references_left(),
             )));
         }
         Ok(())
     }
 
+    /// Returns type of this parsed cell.
+    #[inline]
+    pub const fn cell_type(&self) -> CellType {
+        self.r#type
+    }
+
     /// Returns whether this parsed cell is exotic.
     #[inline]
     pub const fn is_exotic(&self) -> bool {
-        self.is_exotic
+        self.r#type.is_exotic()
     }
 
     /// Returns an error if this parsed cell is not exotic.
@@ -171,6 +379,30 @@ impl<'de> CellParser<'de> {
         Ok(())
     }
 
+    /// Returns an error if this parsed cell is a [`PrunedBranch`](crate::PrunedBranch),
+    /// i.e. its contents were not included into [Merkle proof](crate::MerkleProof).
+    ///
+    /// Reading data from pruned branches always fails with this error.
+    #[inline]
+    pub fn ensure_not_pruned(&self) -> Result<(), CellParserError<'de>> {
+        if self.r#type == CellType::PrunedBranch {
+            return Err(Error::custom(match self.data.get(16..16 + 256) {
+                Some(hash) => format!(
+                    "pruned branch: {}",
+                    hex::encode(hash.to_bitvec().into_vec())
+                ),
+                None => "pruned branch".to_string(),
+            }));
+        }
+        Ok(())
+    }
+
+    /// Data of exotic cell, which can be read even from pruned branches
+    #[inline]
+    pub(crate) fn exotic_data(&mut self) -> &mut &'de BitSlice<u8, Msb0> {
+        &mut self.data
+    }
+
     /// Returns an error if this parsed cell is not ordinary.
     #[inline]
     pub fn ensure_ordinary(&self) -> Result<(), CellParserError<'de>> {
@@ -191,21 +423,25 @@ impl<'de> BitReader<'de> for CellParser<'de> {
 
     #[inline]
     fn read_bit(&mut self) -> Result<Option<bool>, Self::Error> {
+        self.ensure_not_pruned()?;
         self.data.read_bit()
     }
 
     #[inline]
     fn read_bits_into(&mut self, dst: &mut BitSlice<u8, Msb0>) -> Result<usize, Self::Error> {
+        self.ensure_not_pruned()?;
         self.data.read_bits_into(dst)
     }
 
     #[inline]
     fn read_bits(&mut self, n: usize) -> Result<Cow<'de, BitSlice<u8, Msb0>>, Self::Error> {
+        self.ensure_not_pruned()?;
         self.data.read_bits(n)
     }
 
     #[inline]
     fn skip(&mut self, n: usize) -> Result<usize, Self::Error> {
+        self.ensure_not_pruned()?;
         self.data.skip(n)
     }
 }
@@ -216,9 +452,12 @@ impl<'de> CellDeserialize<'de> for CellParser<'de> {
     #[inline]
     fn parse(parser: &mut CellParser<'de>, _: Self::Args) -> Result<Self, CellParserError<'de>> {
         Ok(Self {
-            is_exotic: parser.is_exotic,
+            r#type: parser.r#type,
             data: mem::take(&mut parser.data),
-            references: mem::take(&mut parser.references),
+            references: parser.take_references(),
+            used: parser.used,
+            depth: parser.depth,
+            origin: parser.origin,
         })
     }
 }
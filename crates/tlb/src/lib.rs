@@ -144,8 +144,9 @@ mod boc;
 mod cell;
 pub mod de;
 pub mod ser;
+mod store;
 
-pub use self::{r#as::*, boc::*, cell::*};
+pub use self::{r#as::*, boc::*, cell::*, store::*};
 
 pub use tlbits::{self as bits, Context, Error, StringError, either};
 
@@ -3,8 +3,8 @@ use std::sync::Arc;
 use tlbits::adapters::LimitWriter;
 
 use crate::{
-    Cell, Context, Error,
-    r#as::Ref,
+    Cell, CellArena, Context, Error,
+    r#as::Same,
     bits::{
         bitvec::{order::Msb0, slice::BitSlice, vec::BitVec},
         ser::BitWriter,
@@ -26,6 +26,7 @@ pub struct CellBuilder {
     is_exotic: bool,
     data: CellBitWriter,
     references: Vec<Arc<Cell>>,
+    arena: Option<CellArena>,
 }
 
 pub(crate) const MAX_BITS_LEN: usize = 1023;
@@ -39,6 +40,16 @@ impl CellBuilder {
             is_exotic: false,
             data: LimitWriter::new(BitVec::EMPTY, MAX_BITS_LEN),
             references: Vec::new(),
+            arena: None,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub(crate) fn new_in(arena: CellArena) -> Self {
+        Self {
+            arena: Some(arena),
+            ..Self::new()
         }
     }
 
@@ -135,8 +146,24 @@ impl CellBuilder {
         Ok(())
     }
 
+    /// Store the value with args using its [`CellSerialize`]
+    /// implementation as a reference to a new cell
     #[inline]
-    pub(crate) fn store_reference_as<T, As>(
+    pub fn store_reference<T>(
+        &mut self,
+        value: T,
+        args: T::Args,
+    ) -> Result<&mut Self, CellBuilderError>
+    where
+        T: CellSerialize,
+    {
+        self.store_reference_as::<T, Same>(value, args)
+    }
+
+    /// Store given value with args using an adapter as a reference to
+    /// a new cell
+    #[inline]
+    pub fn store_reference_as<T, As>(
         &mut self,
         value: T,
         args: As::Args,
@@ -145,21 +172,124 @@ impl CellBuilder {
         As: CellSerializeAs<T> + ?Sized,
     {
         self.ensure_reference()?;
-        let mut builder = Self::new();
+        let mut builder = match &self.arena {
+            Some(arena) => Self::new_in(arena.clone()),
+            None => Self::new(),
+        };
         builder.store_as::<T, As>(value, args)?;
-        self.references.push(builder.into_cell().into());
+        let cell = builder.into_cell()?;
+        self.push_reference(cell.into());
         Ok(self)
     }
 
-    /// Convert builder to [`Cell`]
+    /// Store given cell as a reference without copying it
     #[inline]
-    #[must_use]
-    pub fn into_cell(self) -> Cell {
-        Cell {
-            is_exotic: self.is_exotic,
-            data: self.data.into_inner(),
-            references: self.references,
+    pub fn store_reference_cell(&mut self, cell: Arc<Cell>) -> Result<&mut Self, CellBuilderError> {
+        self.ensure_reference()?;
+        self.push_reference(cell);
+        Ok(self)
+    }
+
+    /// Append data and references of another builder, similar to TVM
+    /// `STB`.
+    ///
+    /// Nothing is stored if they do not fit.
+    pub fn store_builder(&mut self, other: &Self) -> Result<&mut Self, CellBuilderError> {
+        if other.data.len() > self.bits_left() || other.references.len() > self.refs_left() {
+            return Err(Error::custom(format!(
+                "builder does not fit: {} bits, {} references, while only {} bits, {} references left",
+                other.data.len(),
+                other.references.len(),
+                self.bits_left(),
+                self.refs_left(),
+            )));
+        }
+        if other.is_exotic {
+            self.exotic()?;
         }
+        self.data.write_bitslice(&other.data)?;
+        for r in &other.references {
+            self.push_reference(r.clone());
+        }
+        Ok(self)
+    }
+
+    /// Same as [`.store()`](CellBuilder::store), but rolls the builder
+    /// back to its previous state on error, so that the value can be
+    /// stored in another way, e.g. as a reference if it does not fit
+    /// inline:
+    /// ```rust
+    /// # use tlb::{bits::ser::BitWriterExt, Cell, Data};
+    /// let mut builder = Cell::builder();
+    /// builder.pack([0u8; 100], ()).unwrap();
+    ///
+    /// let value = [1u8; 50];
+    /// if builder.try_store_as::<_, Data>(value, ()).is_err() {
+    ///     builder.store_reference_as::<_, Data>(value, ()).unwrap();
+    /// }
+    /// assert_eq!(builder.bits_left(), 1023 - 800);
+    /// assert_eq!(builder.refs_left(), 3);
+    /// ```
+    #[inline]
+    pub fn try_store<T>(&mut self, value: T, args: T::Args) -> Result<&mut Self, CellBuilderError>
+    where
+        T: CellSerialize,
+    {
+        self.try_store_as::<T, Same>(value, args)
+    }
+
+    /// Same as [`.store_as()`](CellBuilder::store_as), but rolls the
+    /// builder back to its previous state on error, see
+    /// [`.try_store()`](CellBuilder::try_store)
+    pub fn try_store_as<T, As>(
+        &mut self,
+        value: T,
+        args: As::Args,
+    ) -> Result<&mut Self, CellBuilderError>
+    where
+        As: CellSerializeAs<T> + ?Sized,
+    {
+        let (is_exotic, data, references) =
+            (self.is_exotic, self.data.clone(), self.references.len());
+        if let Err(err) = As::store_as(&value, self, args) {
+            self.is_exotic = is_exotic;
+            self.data = data;
+            self.references.truncate(references);
+            return Err(err);
+        }
+        Ok(self)
+    }
+
+    /// Number of data bits, which can still be stored
+    #[inline]
+    pub fn bits_left(&self) -> usize {
+        self.data.capacity_left()
+    }
+
+    /// Number of references, which can still be stored
+    #[inline]
+    pub fn refs_left(&self) -> usize {
+        MAX_REFS_COUNT - self.references.len()
+    }
+
+    #[inline]
+    fn push_reference(&mut self, cell: Arc<Cell>) {
+        self.references.push(match &self.arena {
+            Some(arena) => arena.intern(cell),
+            None => cell,
+        });
+    }
+
+    /// Convert builder to [`Cell`].
+    ///
+    /// # Errors
+    ///
+    /// If layout of the exotic cell is invalid or its depth exceeds
+    /// [`Cell::MAX_DEPTH`], see [`Cell::from_parts()`]. Previous releases
+    /// returned [`Cell`] without validating it.
+    #[inline]
+    pub fn into_cell(self) -> Result<Cell, CellBuilderError> {
+        Cell::from_parts(self.is_exotic, self.data.into_inner(), self.references)
     }
 }
 
@@ -192,11 +322,73 @@ impl CellSerialize for CellBuilder {
     type Args = ();
 
     fn store(&self, builder: &mut CellBuilder, _: Self::Args) -> Result<(), CellBuilderError> {
-        if self.is_exotic {
-            builder.exotic()?;
-        }
-        builder.write_bitslice(&self.data)?;
-        builder.store_many_as::<_, Ref>(&self.references, ())?;
+        builder.store_builder(self)?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        r#as::{Data, Ref},
+        bits::ser::BitWriterExt,
+        ser::{CellSerializeExt, CellSerializeWrapAsExt},
+    };
+
+    use super::*;
+
+    #[test]
+    fn try_store() {
+        let mut builder = Cell::builder();
+        builder
+            .pack(0xAB_u8, ())
+            .unwrap()
+            .store_reference_as::<_, Data>(1u8, ())
+            .unwrap();
+        let before = builder
+            .store_builder(&Cell::builder())
+            .unwrap()
+            .to_cell(())
+            .unwrap();
+
+        // data fits, while references do not
+        let err = builder.try_store_as::<_, [Ref<Data>; 4]>([2u8; 4], ());
+        assert!(err.is_err());
+        // data does not fit
+        assert!(builder.try_store_as::<_, Data>([0u8; 128], ()).is_err());
+        assert_eq!(builder.to_cell(()).unwrap(), before);
+        assert_eq!(builder.bits_left(), MAX_BITS_LEN - 8);
+        assert_eq!(builder.refs_left(), 3);
+
+        builder.try_store(0xCD_u8.wrap_as::<Data>(), ()).unwrap();
+        assert_eq!(builder.bits_left(), MAX_BITS_LEN - 16);
+    }
+
+    #[test]
+    fn store_builder() {
+        let mut a = Cell::builder();
+        a.pack(0xAB_u8, ()).unwrap();
+        a.store_reference_as::<_, Data>(1u8, ()).unwrap();
+        let mut b = Cell::builder();
+        b.pack(0xCD_u8, ()).unwrap();
+        b.store_reference(2u8.wrap_as::<Data>(), ()).unwrap();
+
+        a.store_builder(&b).unwrap();
+        assert_eq!(
+            a.into_cell().unwrap(),
+            (
+                0xABCD_u16.wrap_as::<Data>(),
+                1u8.wrap_as::<Ref<Data>>(),
+                2u8.wrap_as::<Ref<Data>>()
+            )
+                .to_cell(((), (), ()))
+                .unwrap()
+        );
+
+        let mut full = Cell::builder();
+        full.repeat_bit(MAX_BITS_LEN - 4, false).unwrap();
+        assert!(full.store_builder(&b).is_err());
+        assert_eq!(full.bits_left(), 4);
+        assert_eq!(full.refs_left(), MAX_REFS_COUNT);
+    }
+}
@@ -4,12 +4,16 @@ mod builder;
 
 pub use self::{r#as::*, builder::*};
 
+/// Derive [`CellSerialize`] implementation, requires `derive` feature
+#[cfg(feature = "derive")]
+pub use tlbits_derive::CellSerialize;
+
 use std::{borrow::Cow, rc::Rc, sync::Arc};
 
 use impl_tools::autoimpl;
 use tlbits::ser::BitWriter;
 
-use crate::{Cell, Context, Ref, Same, bits::ser::BitWriterExt, either::Either};
+use crate::{Cell, CellArena, Context, Same, bits::ser::BitWriterExt, either::Either};
 
 /// A type that can be **ser**ialized.  
 #[autoimpl(for<T: trait + ToOwned + ?Sized> Cow<'_, T>)]
@@ -132,11 +136,15 @@ impl CellSerialize for Cell {
 
     #[inline]
     fn store(&self, builder: &mut CellBuilder, _: Self::Args) -> Result<(), CellBuilderError> {
-        if self.is_exotic {
+        if self.is_exotic() {
             builder.exotic()?;
         }
-        builder.write_bitslice(&self.data)?;
-        builder.store_many_as::<_, Ref>(&self.references, ())?;
+        builder.write_bitslice(self.data())?;
+        for (i, r) in self.references().iter().enumerate() {
+            builder
+                .store_reference_cell(r.clone())
+                .with_context(|| format!("[{i}]"))?;
+        }
 
         Ok(())
     }
@@ -147,7 +155,21 @@ pub trait CellSerializeExt: CellSerialize {
     fn to_cell(&self, args: Self::Args) -> Result<Cell, CellBuilderError> {
         let mut builder = Cell::builder();
         self.store(&mut builder, args)?;
-        Ok(builder.into_cell())
+        builder.into_cell()
+    }
+
+    /// Same as [`.to_cell()`](CellSerializeExt::to_cell), but returns
+    /// canonical instance of the cell from given [`CellArena`], so that
+    /// it shares equal subtrees with other cells interned there
+    #[inline]
+    fn to_cell_in(
+        &self,
+        args: Self::Args,
+        arena: &CellArena,
+    ) -> Result<Arc<Cell>, CellBuilderError> {
+        let mut builder = Cell::builder_in(arena);
+        self.store(&mut builder, args)?;
+        Ok(arena.intern(builder.into_cell()?))
     }
 }
 impl<T> CellSerializeExt for T where T: CellSerialize {}
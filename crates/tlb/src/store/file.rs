@@ -0,0 +1,280 @@
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, ErrorKind, Read, Seek, SeekFrom, Write},
+    path::Path,
+    sync::{Mutex, PoisonError},
+};
+
+use crate::{
+    Context,
+    bits::bitvec::{order::Msb0, vec::BitVec},
+};
+
+use super::{CellStore, StoredCell};
+
+/// Append-only file-backed [`CellStore`].
+///
+/// Cells are appended to the file as records:
+/// ```tlb
+/// record hash:bits256 d1:uint8 d2:uint8 data:(ceil(d2 / 2) * uint8)
+///   references:(refs * bits256) = Record;
+/// ```
+/// where `d1 = refs + 8 * is_exotic` and `d2` and `data` are encoded the
+/// same way as in [`BagOfCells`](crate::BagOfCells).
+///
+/// Only an index of offsets is kept in memory, while cells themselves
+/// are read from the file when requested.
+#[derive(Debug)]
+pub struct FileCellStore {
+    file: Mutex<File>,
+    index: HashMap<[u8; 32], u64>,
+}
+
+impl FileCellStore {
+    /// Open existing store or create a new one at given path.
+    ///
+    /// Incomplete trailing record, e.g. left by an interrupted write, is
+    /// truncated.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+
+        let mut index = HashMap::new();
+        let mut reader = BufReader::new(&mut file);
+        let mut offset = 0;
+        while !reader.fill_buf()?.is_empty() {
+            let (hash, cell) = match read_record(&mut reader) {
+                Ok(record) => record,
+                Err(err) if err.kind() == ErrorKind::UnexpectedEof => break,
+                Err(err) => return Err(err).with_context(|| format!("record at {offset}")),
+            };
+            index.insert(hash, offset);
+            offset += record_len(&cell) as u64;
+        }
+        if file.metadata()?.len() > offset {
+            file.set_len(offset)?;
+        }
+
+        Ok(Self {
+            file: Mutex::new(file),
+            index,
+        })
+    }
+
+    /// Number of stored cells
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Flush all appended cells to disk
+    #[inline]
+    pub fn sync(&self) -> io::Result<()> {
+        self.file
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .sync_data()
+    }
+}
+
+impl CellStore for FileCellStore {
+    type Error = io::Error;
+
+    fn get_raw(&self, hash: &[u8; 32]) -> Result<Option<StoredCell>, Self::Error> {
+        let Some(&offset) = self.index.get(hash) else {
+            return Ok(None);
+        };
+        let mut file = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+        file.seek(SeekFrom::Start(offset))?;
+        let (stored_hash, cell) =
+            read_record(&mut *file).with_context(|| format!("record at {offset}"))?;
+        if stored_hash != *hash {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!("record at {offset}: hash mismatch"),
+            ));
+        }
+        Ok(Some(cell))
+    }
+
+    fn put_raw(&mut self, hash: [u8; 32], cell: StoredCell) -> Result<(), Self::Error> {
+        if self.index.contains_key(&hash) {
+            return Ok(());
+        }
+        let file = self.file.get_mut().unwrap_or_else(PoisonError::into_inner);
+        let offset = file.seek(SeekFrom::End(0))?;
+        file.write_all(&encode_record(hash, &cell)?)?;
+        self.index.insert(hash, offset);
+        Ok(())
+    }
+
+    #[inline]
+    fn contains(&self, hash: &[u8; 32]) -> Result<bool, Self::Error> {
+        Ok(self.index.contains_key(hash))
+    }
+}
+
+#[inline]
+fn record_len(cell: &StoredCell) -> usize {
+    32 + 2 + cell.data.len().div_ceil(8) + cell.references.len() * 32
+}
+
+fn encode_record(hash: [u8; 32], cell: &StoredCell) -> io::Result<Vec<u8>> {
+    let bits = cell.data.len();
+    if bits > 1023 || cell.references.len() > 4 {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "cell is too big: {bits} bits, {} references",
+                cell.references.len()
+            ),
+        ));
+    }
+    let mut buf = Vec::with_capacity(record_len(cell));
+    buf.extend_from_slice(&hash);
+    buf.push(cell.references.len() as u8 | (cell.is_exotic as u8) << 3);
+    buf.push((bits / 8 + bits.div_ceil(8)) as u8);
+    let mut data = cell.data.clone();
+    if bits % 8 != 0 {
+        // completion tag
+        data.push(true);
+        data.resize(bits.next_multiple_of(8), false);
+    }
+    buf.extend_from_slice(data.as_raw_slice());
+    for r in &cell.references {
+        buf.extend_from_slice(r);
+    }
+    Ok(buf)
+}
+
+fn read_record(mut reader: impl Read) -> io::Result<([u8; 32], StoredCell)> {
+    let mut hash = [0; 32];
+    reader.read_exact(&mut hash)?;
+    let mut descriptors = [0; 2];
+    reader.read_exact(&mut descriptors)?;
+    let [d1, d2] = descriptors;
+    let refs = (d1 & 0b111) as usize;
+    if refs > 4 || d1 & !0b1111 != 0 {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            format!("invalid descriptors: {d1:#04x} {d2:#04x}"),
+        ));
+    }
+    let mut data = vec![0; (d2 as usize).div_ceil(2)];
+    reader.read_exact(&mut data)?;
+    let mut data = BitVec::<u8, Msb0>::from_vec(data);
+    if d2 % 2 == 1 {
+        // strip completion tag
+        let trailing = data.trailing_zeros();
+        if trailing >= 8 {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                "missing completion tag",
+            ));
+        }
+        data.truncate(data.len() - trailing - 1);
+    }
+    let mut references = vec![[0; 32]; refs];
+    for r in &mut references {
+        reader.read_exact(r)?;
+    }
+    Ok((
+        hash,
+        StoredCell {
+            is_exotic: d1 & 0b1000 != 0,
+            data,
+            references,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use crate::{
+        Cell, Data, Ref,
+        ser::{CellSerializeExt, CellSerializeWrapAsExt},
+    };
+
+    use super::{super::tests::tree, *};
+
+    #[test]
+    fn reopen() {
+        let path = env::temp_dir().join(format!("tlb-cell-store-{}", process::id()));
+        let _ = fs::remove_file(&path);
+
+        let root = tree();
+        let hash = {
+            let mut store = FileCellStore::open(&path).unwrap();
+            let hash = store.put(&root).unwrap();
+            assert_eq!(store.len(), 4);
+            store.sync().unwrap();
+            hash
+        };
+
+        let mut store = FileCellStore::open(&path).unwrap();
+        assert_eq!(store.len(), 4);
+        assert_eq!(*store.get(&hash).unwrap().unwrap(), root);
+        assert_eq!(
+            store.get_lazy(hash).reference(0).unwrap().data().unwrap(),
+            root.references()[0].data(),
+        );
+        let a: Cell = store
+            .get_lazy(hash)
+            .parser()
+            .unwrap()
+            .parse_as::<_, Ref>(())
+            .unwrap();
+        assert_eq!(a, *root.references()[0]);
+
+        // already stored cells are not appended again
+        let len = fs::metadata(&path).unwrap().len();
+        store.put(&root).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().len(), len);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn torn_record() {
+        let path = env::temp_dir().join(format!("tlb-cell-store-torn-{}", process::id()));
+        let _ = fs::remove_file(&path);
+
+        let root = tree();
+        let hash = FileCellStore::open(&path).unwrap().put(&root).unwrap();
+        let len = fs::metadata(&path).unwrap().len();
+
+        // interrupted write of the next record
+        let leaf = 0xAB_u8.wrap_as::<Data>().to_cell(()).unwrap();
+        let record = encode_record(leaf.hash(), &(&leaf).into()).unwrap();
+        OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(&record[..record.len() - 1])
+            .unwrap();
+
+        let mut store = FileCellStore::open(&path).unwrap();
+        assert_eq!(store.len(), 4);
+        assert_eq!(fs::metadata(&path).unwrap().len(), len);
+        assert_eq!(*store.get(&hash).unwrap().unwrap(), root);
+
+        let leaf_hash = store.put(&leaf).unwrap();
+        drop(store);
+        let store = FileCellStore::open(&path).unwrap();
+        assert_eq!(store.len(), 5);
+        assert_eq!(*store.get(&leaf_hash).unwrap().unwrap(), leaf);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
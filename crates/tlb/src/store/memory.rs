@@ -0,0 +1,50 @@
+use std::collections::HashMap;
+
+use crate::StringError;
+
+use super::{CellStore, StoredCell};
+
+/// In-memory [`CellStore`]
+#[derive(Debug, Clone, Default)]
+pub struct MemoryCellStore {
+    cells: HashMap<[u8; 32], StoredCell>,
+}
+
+impl MemoryCellStore {
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of stored cells
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+}
+
+impl CellStore for MemoryCellStore {
+    type Error = StringError;
+
+    #[inline]
+    fn get_raw(&self, hash: &[u8; 32]) -> Result<Option<StoredCell>, Self::Error> {
+        Ok(self.cells.get(hash).cloned())
+    }
+
+    #[inline]
+    fn put_raw(&mut self, hash: [u8; 32], cell: StoredCell) -> Result<(), Self::Error> {
+        self.cells.insert(hash, cell);
+        Ok(())
+    }
+
+    #[inline]
+    fn contains(&self, hash: &[u8; 32]) -> Result<bool, Self::Error> {
+        Ok(self.cells.contains_key(hash))
+    }
+}
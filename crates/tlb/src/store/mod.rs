@@ -0,0 +1,414 @@
+//! Content-addressed storage for [`Cell`]s
+mod file;
+mod memory;
+
+pub use self::{file::*, memory::*};
+
+use std::{cell::OnceCell, collections::HashMap, sync::Arc};
+
+use crate::{
+    Cell, Error, StringError,
+    bits::bitvec::{order::Msb0, slice::BitSlice, vec::BitVec},
+    de::{CellParser, LazyReferences},
+};
+
+/// Content of a single cell in [`CellStore`], where its references are
+/// represented by their representation [hashes](Cell::hash)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StoredCell {
+    pub is_exotic: bool,
+    pub data: BitVec<u8, Msb0>,
+    pub references: Vec<[u8; 32]>,
+}
+
+impl From<&Cell> for StoredCell {
+    #[inline]
+    fn from(cell: &Cell) -> Self {
+        Self {
+            is_exotic: cell.is_exotic(),
+            data: cell.data().to_bitvec(),
+            references: cell.references().iter().map(|r| Cell::hash(r)).collect(),
+        }
+    }
+}
+
+/// Content-addressed storage of cell trees, where each cell is stored
+/// once by its representation [hash](Cell::hash).
+///
+/// Implementations only need to provide access to individual
+/// [`StoredCell`]s, while whole trees are stored and loaded with
+/// [`.put()`](CellStore::put) and [`.get()`](CellStore::get), or
+/// traversed on demand with [`.get_lazy()`](CellStore::get_lazy), so that
+/// huge trees do not need to be loaded whole.
+/// ```rust
+/// # use tlb::{
+/// #     ser::{CellSerializeExt, CellSerializeWrapAsExt},
+/// #     CellStore, Data, MemoryCellStore, Ref, StringError,
+/// # };
+/// # fn main() -> Result<(), StringError> {
+/// let cell = (1u8.wrap_as::<Data>(), 2u8.wrap_as::<Ref<Data>>()).to_cell(((), ()))?;
+///
+/// let mut store = MemoryCellStore::new();
+/// let hash = store.put(&cell)?;
+/// assert_eq!(store.len(), 2);
+///
+/// let lazy = store.get_lazy(hash);
+/// assert_eq!(lazy.reference(0)?.data()?, cell.references()[0].data());
+///
+/// let loaded = store.get(&hash)?.unwrap();
+/// assert_eq!(*loaded, cell);
+/// # Ok(())
+/// # }
+/// ```
+pub trait CellStore {
+    type Error: Error;
+
+    /// Return content of the cell with given hash, if present
+    fn get_raw(&self, hash: &[u8; 32]) -> Result<Option<StoredCell>, Self::Error>;
+
+    /// Store content of the cell with given hash.
+    ///
+    /// Callers should put referenced cells before the ones referencing
+    /// them, so that presence of a cell implies presence of its subtree.
+    fn put_raw(&mut self, hash: [u8; 32], cell: StoredCell) -> Result<(), Self::Error>;
+
+    /// Returns whether cell with given hash is present
+    #[inline]
+    fn contains(&self, hash: &[u8; 32]) -> Result<bool, Self::Error> {
+        self.get_raw(hash).map(|cell| cell.is_some())
+    }
+
+    /// Store given cell with its whole subtree and return its hash.
+    ///
+    /// Subtrees which are already present are skipped.
+    fn put(&mut self, cell: &Cell) -> Result<[u8; 32], Self::Error> {
+        let hash = cell.hash();
+        if self.contains(&hash)? {
+            return Ok(hash);
+        }
+        for r in cell.references() {
+            self.put(r)?;
+        }
+        self.put_raw(hash, cell.into())?;
+        Ok(hash)
+    }
+
+    /// Load cell with given hash with its whole subtree.
+    ///
+    /// Hashes of loaded cells are verified against the ones they are
+    /// stored with.
+    fn get(&self, hash: &[u8; 32]) -> Result<Option<Arc<Cell>>, Self::Error> {
+        load(self, *hash, &mut HashMap::new())
+    }
+
+    /// Return a handle to the cell with given hash, which loads it and
+    /// its references only when accessed
+    #[inline]
+    fn get_lazy(&self, hash: [u8; 32]) -> LazyCell<'_, Self> {
+        LazyCell::new(self, hash)
+    }
+}
+
+fn load<S>(
+    store: &S,
+    hash: [u8; 32],
+    loaded: &mut HashMap<[u8; 32], Arc<Cell>>,
+) -> Result<Option<Arc<Cell>>, S::Error>
+where
+    S: CellStore + ?Sized,
+{
+    if let Some(cell) = loaded.get(&hash) {
+        return Ok(Some(cell.clone()));
+    }
+    let Some(stored) = store.get_raw(&hash)? else {
+        return Ok(None);
+    };
+    let references = stored
+        .references
+        .iter()
+        .map(|r| {
+            load(store, *r, loaded)?
+                .ok_or_else(|| Error::custom(format!("missing reference {}", hex::encode(r))))
+        })
+        .collect::<Result<_, _>>()
+        .map_err(|err: S::Error| err.context(hex::encode(hash)))?;
+    let cell: Arc<Cell> = Cell::from_parts(stored.is_exotic, stored.data, references)
+        .map_err(|err| Error::custom(format!("{}: {err}", hex::encode(hash))))?
+        .into();
+    if Cell::hash(&cell) != hash {
+        return Err(Error::custom(format!(
+            "{}: hash mismatch, got: {}",
+            hex::encode(hash),
+            hex::encode(Cell::hash(&cell)),
+        )));
+    }
+    loaded.insert(hash, cell.clone());
+    Ok(Some(cell))
+}
+
+/// Cell of [`CellStore`] loaded on demand.
+///
+/// [`.data()`](LazyCell::data) fetches only this cell, while
+/// [`.reference()`](LazyCell::reference) returns another [`LazyCell`]
+/// without fetching it. Fetched cells are kept along with the handle,
+/// so that the tree is paged in from the store only as it is accessed.
+///
+/// It can be parsed with [`.parser()`](LazyCell::parser), which resolves
+/// references through the store only when they are parsed:
+/// ```rust
+/// # use tlb::{
+/// #     ser::{CellSerializeExt, CellSerializeWrapAsExt},
+/// #     CellStore, Data, MemoryCellStore, Ref, StringError,
+/// # };
+/// # fn main() -> Result<(), StringError> {
+/// let cell = (1u8.wrap_as::<Ref<Data>>(), 2u8.wrap_as::<Ref<Data>>()).to_cell(((), ()))?;
+///
+/// let mut store = MemoryCellStore::new();
+/// let hash = store.put(&cell)?;
+///
+/// let lazy = store.get_lazy(hash);
+/// let mut parser = lazy.parser()?;
+/// assert_eq!(parser.parse_as::<u8, Ref<Data>>(())?, 1);
+/// # Ok(())
+/// # }
+/// ```
+pub struct LazyCell<'s, S: ?Sized> {
+    store: &'s S,
+    hash: [u8; 32],
+    stored: OnceCell<StoredCell>,
+    references: OnceCell<Vec<Self>>,
+}
+
+impl<'s, S> LazyCell<'s, S>
+where
+    S: CellStore + ?Sized,
+{
+    #[inline]
+    fn new(store: &'s S, hash: [u8; 32]) -> Self {
+        Self {
+            store,
+            hash,
+            stored: OnceCell::new(),
+            references: OnceCell::new(),
+        }
+    }
+
+    /// Representation hash of this cell
+    #[inline]
+    pub fn hash(&self) -> [u8; 32] {
+        self.hash
+    }
+
+    fn stored(&self) -> Result<&StoredCell, S::Error> {
+        if let Some(stored) = self.stored.get() {
+            return Ok(stored);
+        }
+        let stored = self
+            .store
+            .get_raw(&self.hash)?
+            .ok_or_else(|| Error::custom(format!("{}: not found", hex::encode(self.hash))))?;
+        Ok(self.stored.get_or_init(|| stored))
+    }
+
+    #[inline]
+    pub fn is_exotic(&self) -> Result<bool, S::Error> {
+        self.stored().map(|stored| stored.is_exotic)
+    }
+
+    /// Data bits of this cell
+    #[inline]
+    pub fn data(&self) -> Result<&BitSlice<u8, Msb0>, S::Error> {
+        self.stored().map(|stored| stored.data.as_bitslice())
+    }
+
+    /// Number of references
+    #[inline]
+    pub fn references_len(&self) -> Result<usize, S::Error> {
+        self.stored().map(|stored| stored.references.len())
+    }
+
+    /// Return handle to `i`-th reference without loading it
+    pub fn reference(&self, i: usize) -> Result<&Self, S::Error> {
+        self.references()?
+            .get(i)
+            .ok_or_else(|| Error::custom(format!("reference out of bounds: {i}")))
+    }
+
+    /// Return handles to all references without loading them
+    pub fn references(&self) -> Result<&[Self], S::Error> {
+        if let Some(references) = self.references.get() {
+            return Ok(references);
+        }
+        let references = self
+            .stored()?
+            .references
+            .iter()
+            .map(|hash| Self::new(self.store, *hash))
+            .collect();
+        Ok(self.references.get_or_init(|| references))
+    }
+
+    /// Return [`CellParser`] for this cell, which fetches references
+    /// from the store only when they are parsed.
+    ///
+    /// Unlike [`.load()`](LazyCell::load), hashes of parsed cells are not
+    /// verified, unless they are parsed into [`Cell`].
+    pub fn parser(&self) -> Result<CellParser<'_>, S::Error> {
+        let stored = self.stored()?;
+        self.references()?;
+        CellParser::lazy(stored.is_exotic, &stored.data, self)
+            .map_err(|err| Error::custom(format!("{}: {err}", hex::encode(self.hash))))
+    }
+
+    /// Load this cell with its whole subtree, see [`CellStore::get()`]
+    #[inline]
+    pub fn load(&self) -> Result<Arc<Cell>, S::Error> {
+        self.store
+            .get(&self.hash)?
+            .ok_or_else(|| Error::custom(format!("{}: not found", hex::encode(self.hash))))
+    }
+}
+
+impl<S> LazyReferences for LazyCell<'_, S>
+where
+    S: CellStore + ?Sized,
+{
+    #[inline]
+    fn references_len(&self) -> usize {
+        self.references.get().map_or(0, Vec::len)
+    }
+
+    #[inline]
+    fn reference_parser(&self, i: usize) -> Result<CellParser<'_>, StringError> {
+        self.reference(i)
+            .and_then(Self::parser)
+            .map_err(Error::custom)
+    }
+
+    #[inline]
+    fn load_reference(&self, i: usize) -> Result<Arc<Cell>, StringError> {
+        self.reference(i)
+            .and_then(Self::load)
+            .map_err(Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        BagOfCells,
+        r#as::{Data, Ref},
+        ser::{CellSerializeExt, CellSerializeWrapAsExt},
+    };
+
+    use super::*;
+
+    /// ```text
+    /// root -> { a -> { c }, b -> { c } }
+    /// ```
+    pub(super) fn tree() -> Cell {
+        let c: Arc<Cell> = [0xC_u8; 3]
+            .wrap_as::<Data>()
+            .to_cell(())
+            .map(Arc::new)
+            .unwrap();
+        let node = |v: u8| {
+            (v.wrap_as::<Data>(), c.wrap_as::<Ref>())
+                .to_cell(((), ()))
+                .unwrap()
+        };
+        (node(0xA).wrap_as::<Ref>(), node(0xB).wrap_as::<Ref>())
+            .to_cell(((), ()))
+            .unwrap()
+    }
+
+    #[test]
+    fn memory() {
+        let root = tree();
+        let mut store = MemoryCellStore::new();
+        let hash = store.put(&root).unwrap();
+        assert_eq!(hash, root.hash());
+        assert_eq!(store.len(), 4);
+        assert_eq!(store.put(&root).unwrap(), hash);
+        assert_eq!(store.len(), 4);
+
+        let loaded = store.get(&hash).unwrap().unwrap();
+        assert_eq!(*loaded, root);
+        assert!(Arc::ptr_eq(
+            &loaded.references()[0].references()[0],
+            &loaded.references()[1].references()[0],
+        ));
+        assert!(store.get(&[0; 32]).unwrap().is_none());
+
+        let lazy = store.get_lazy(hash);
+        assert_eq!(lazy.references_len().unwrap(), 2);
+        let b = lazy.reference(1).unwrap();
+        assert_eq!(b.hash(), root.references()[1].hash());
+        assert_eq!(*b.load().unwrap(), *root.references()[1]);
+    }
+
+    #[test]
+    fn lazy_parser() {
+        /// Counts fetched cells
+        struct Counting(MemoryCellStore, core::cell::Cell<usize>);
+
+        impl CellStore for Counting {
+            type Error = StringError;
+
+            fn get_raw(&self, hash: &[u8; 32]) -> Result<Option<StoredCell>, Self::Error> {
+                self.1.set(self.1.get() + 1);
+                self.0.get_raw(hash)
+            }
+
+            fn put_raw(&mut self, hash: [u8; 32], cell: StoredCell) -> Result<(), Self::Error> {
+                self.0.put_raw(hash, cell)
+            }
+        }
+
+        let root = tree();
+        let mut store = Counting(MemoryCellStore::new(), Default::default());
+        let hash = store.put(&root).unwrap();
+        store.1.set(0);
+
+        let lazy = store.get_lazy(hash);
+        let mut parser = lazy.parser().unwrap();
+        let a: (u8, [u8; 3]) = parser
+            .parse_as::<_, Ref<(Data, Ref<Data>)>>(((), ()))
+            .unwrap();
+        assert_eq!(a, (0xA, [0xC; 3]));
+        // root, a and c
+        assert_eq!(store.1.get(), 3);
+
+        // fetched cells are kept
+        let mut parser = lazy.parser().unwrap();
+        parser
+            .parse_as::<(u8, [u8; 3]), Ref<(Data, Ref<Data>)>>(((), ()))
+            .unwrap();
+        assert_eq!(store.1.get(), 3);
+
+        let b: Cell = parser.parse_as::<_, Ref>(()).unwrap();
+        assert_eq!(b, *root.references()[1]);
+        parser.ensure_empty().unwrap();
+    }
+
+    #[test]
+    fn hash_mismatch() {
+        let root = tree();
+        let mut store = MemoryCellStore::new();
+        store.put(&root).unwrap();
+        let mut tampered = store.get_raw(&root.hash()).unwrap().unwrap();
+        tampered.data.push(true);
+        store.put_raw(root.hash(), tampered).unwrap();
+
+        let err = store.get(&root.hash()).unwrap_err();
+        assert!(err.to_string().contains("hash mismatch"), "{err}");
+    }
+
+    #[test]
+    fn boc() {
+        let boc = BagOfCells::from_root(tree());
+        let mut store = MemoryCellStore::new();
+        let roots = boc.store_in(&mut store).unwrap();
+        assert_eq!(BagOfCells::load_from(&store, roots).unwrap(), boc);
+    }
+}